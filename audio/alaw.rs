@@ -1,41 +1,111 @@
-const A: f32 = 87.6;
-const MAX_AMPLITUDE: f32 = 32767.0;
-
-fn a_law_encode(sample: i16) -> u8 {
-    let sample = sample as f32;
-    let sign = if sample < 0.0 { 0x80 } else { 0x00 };
-    let abs_sample = sample.abs();
-    let compressed_sample = if abs_sample < 1.0 / A {
-        A * abs_sample / (1.0 + A.log2())
-    } else {
-        1.0 + abs_sample.log2() / (1.0 + A.log2())
-    };
-    let quantized_sample = ((compressed_sample * 15.0).round() as u8) & 0x0F;
-    sign | (quantized_sample << 4)
+// ITU-T G.711 A-law companding of 16-bit linear PCM. Samples are reduced to
+// 13 bits and coded as a sign bit, a 3-bit segment (exponent) and a 4-bit
+// step within the segment; even bits are inverted on the line (XOR 0x55).
+
+const SIGN_BIT: u8 = 0x80;
+const QUANT_MASK: u8 = 0x0F;
+const SEG_SHIFT: u8 = 4;
+const SEG_MASK: u8 = 0x70;
+
+// Largest 13-bit magnitude in each segment
+const SEG_END: [i16; 8] = [0x1F, 0x3F, 0x7F, 0xFF, 0x1FF, 0x3FF, 0x7FF, 0xFFF];
+
+pub fn a_law_encode(sample: i16) -> u8 {
+    let pcm = sample >> 3;
+    // Negative values are folded onto 0..=4095 by one's complement
+    let (mask, magnitude) = if pcm >= 0 { (0xD5, pcm) } else { (0x55, -pcm - 1) };
+
+    let segment = SEG_END.iter().position(|&end| magnitude <= end).unwrap_or(8);
+    if segment >= 8 {
+        return 0x7F ^ mask;
+    }
+
+    // The first two segments share the same step size
+    let shift = if segment < 2 { 1 } else { segment };
+    let step = (magnitude >> shift) as u8 & QUANT_MASK;
+    (((segment as u8) << SEG_SHIFT) | step) ^ mask
 }
 
-fn a_law_decode(encoded_sample: u8) -> i16 {
-    let sign = if encoded_sample & 0x80 == 0x80 { -1 } else { 1 };
-    let quantized_sample = ((encoded_sample & 0x0F) as f32) / 15.0;
-    let abs_sample = if quantized_sample < 1.0 / A {
-        quantized_sample * (1.0 + A.log2()) / A
+pub fn a_law_decode(encoded_sample: u8) -> i16 {
+    let a_val = encoded_sample ^ 0x55;
+    let segment = (a_val & SEG_MASK) >> SEG_SHIFT;
+
+    // Reconstruct at the middle of the step
+    let mut magnitude = ((a_val & QUANT_MASK) as i16) << 4;
+    magnitude += if segment == 0 { 8 } else { 0x108 };
+    if segment > 1 {
+        magnitude <<= segment - 1;
+    }
+
+    if a_val & SIGN_BIT != 0 {
+        magnitude
     } else {
-        (1.0 + A.log2()).exp2() * (quantized_sample - 1.0)
-    };
-    ((sign as f32 * abs_sample * MAX_AMPLITUDE).round() as i16).wrapping_mul(sign)
+        -magnitude
+    }
 }
 
-fn main() {
-    test_a_law();
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Values from the ITU-T / Sun reference codec (as in Python's audioop)
+    #[test]
+    fn encode_known_answers() {
+        let test_cases = [
+            (0, 0xD5),
+            (-1, 0x55),
+            (8, 0xD5),
+            (-8, 0x55),
+            (100, 0xD3),
+            (-100, 0x53),
+            (1000, 0xFA),
+            (-1000, 0x7A),
+            (4096, 0x85),
+            (32767, 0xAA),
+            (-32768, 0x2A),
+        ];
 
-fn test_a_law() {
-    let samples = vec![-32768, -16384, -8192, -4096, -2048, -1024, -512, -256, 0, 256, 512, 1024, 2048, 4096, 8192, 16384, 32767];
-    for &sample in &samples {
-        let encoded = a_law_encode(sample);
-        let decoded = a_law_decode(encoded);
-        println!("Original: {}, Encoded: {:02X}, Decoded: {}", sample, encoded, decoded);
-        assert!((sample - decoded).abs() <= (MAX_AMPLITUDE / 15.0) as i16);
+        for (sample, expected) in test_cases {
+            assert_eq!(a_law_encode(sample), expected, "sample {}", sample);
+        }
+    }
+
+    #[test]
+    fn decode_known_answers() {
+        let test_cases = [
+            (0xD5, 8),
+            (0x55, -8),
+            (0xAA, 32256),
+            (0x2A, -32256),
+            (0x80, 5504),
+            (0x00, -5504),
+            (0xFF, 848),
+            (0x7F, -848),
+        ];
+
+        for (code, expected) in test_cases {
+            assert_eq!(a_law_decode(code), expected, "code {:02X}", code);
+        }
+    }
+
+    #[test]
+    fn every_code_round_trips() {
+        for code in 0..=255u8 {
+            assert_eq!(a_law_encode(a_law_decode(code)), code, "code {:02X}", code);
+        }
     }
-}
 
+    #[test]
+    fn every_sample_round_trips_within_one_step() {
+        let mut previous = i16::MIN;
+        for sample in i16::MIN..=i16::MAX {
+            let decoded = a_law_decode(a_law_encode(sample));
+            // Steps grow with the segment, keeping the error near 1/32 of
+            // the sample; small samples are quantised in steps of 16
+            let error = (sample as i32 - decoded as i32).unsigned_abs();
+            assert!(error <= sample.unsigned_abs() as u32 / 32 + 8, "sample {} decoded {}", sample, decoded);
+            assert!(decoded >= previous, "not monotonic at {}", sample);
+            previous = decoded;
+        }
+    }
+}
@@ -0,0 +1,4 @@
+// Audio codecs. Every codec is a cargo feature, all on by default.
+
+#[cfg(feature = "alaw")]
+pub mod alaw;
@@ -1,10 +1,10 @@
 const ALPHABET_SIZE: u8 = 95;
 const Z95_CONVERSION_CONSTANT: u8 = 32;
 
-fn modular_inverse(a: i32, m: i32) -> Result<i32, &'static str> {
+pub fn modular_inverse(a: i32, m: i32) -> Result<i32, &'static str> {
     let mut x0 = 1;
     let mut x1 = 0;
-    let mut original_m = m;
+    let original_m = m;
 
     let mut a = a;
     let mut m = m;
@@ -31,74 +31,155 @@ fn modular_inverse(a: i32, m: i32) -> Result<i32, &'static str> {
     Ok(x0)
 }
 
-struct AffineCipher {
+pub struct AffineCipher {
     a: i32,
     b: i32,
+    // Found once in new(), so decryption cannot fail
+    a_inverse: i32,
 }
 
 impl AffineCipher {
-    fn new(a: i32, b: i32) -> AffineCipher {
-        AffineCipher { a, b }
+    // 'a' must be coprime with the alphabet size, or decryption is impossible
+    pub fn new(a: i32, b: i32) -> Result<AffineCipher, &'static str> {
+        let a = a.rem_euclid(ALPHABET_SIZE as i32);
+        let a_inverse = modular_inverse(a, ALPHABET_SIZE as i32)?;
+        Ok(AffineCipher {
+            a,
+            b: b.rem_euclid(ALPHABET_SIZE as i32),
+            a_inverse,
+        })
     }
 
-    fn encrypt(&self, plaintext: &str) -> String {
+    // Position of a printable ASCII character in the alphabet; anything else
+    // passes through unchanged
+    fn char_code(c: char) -> Option<i32> {
+        (' '..='~')
+            .contains(&c)
+            .then(|| c as i32 - Z95_CONVERSION_CONSTANT as i32)
+    }
+
+    fn from_char_code(char_code: i32) -> char {
+        (char_code.rem_euclid(ALPHABET_SIZE as i32) as u8 + Z95_CONVERSION_CONSTANT) as char
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> String {
         let mut ciphertext = String::new();
         for c in plaintext.chars() {
-            let char_code = c as i32 - Z95_CONVERSION_CONSTANT as i32;
-            let char_code = ((char_code * self.a) + self.b) % ALPHABET_SIZE as i32;
-            ciphertext.push((char_code + Z95_CONVERSION_CONSTANT as i32) as u8 as char);
+            ciphertext.push(match Self::char_code(c) {
+                Some(char_code) => Self::from_char_code(char_code * self.a + self.b),
+                None => c,
+            });
         }
         ciphertext
     }
 
-    fn decrypt(&self, ciphertext: &str) -> Result<String, &'static str> {
-        let a_inverse = modular_inverse(self.a, ALPHABET_SIZE as i32)?;
-        let b_inverse = -(self.b % ALPHABET_SIZE as i32) + ALPHABET_SIZE as i32;
+    pub fn decrypt(&self, ciphertext: &str) -> String {
         let mut plaintext = String::new();
-
         for c in ciphertext.chars() {
-            let char_code = c as i32 - Z95_CONVERSION_CONSTANT as i32;
-            let char_code = (a_inverse * (char_code + b_inverse)) % ALPHABET_SIZE as i32;
-            plaintext.push((char_code + Z95_CONVERSION_CONSTANT as i32) as u8 as char);
+            plaintext.push(match Self::char_code(c) {
+                Some(char_code) => Self::from_char_code(self.a_inverse * (char_code - self.b)),
+                None => c,
+            });
         }
-
-        Ok(plaintext)
+        plaintext
     }
 }
 
-fn test_affine() {
-    let test_cases = vec![
-        ("Hello!", "&3ddy2", 7, 11),
-        ("TheAlgorithms/C", "DNC}=jHS2zN!7;E", 67, 67),
-        ("0123456789", "840,($ {ws", 91, 88),
-        ("7W@;cdeRT9uL", "JDfa*we?z&bL", 77, 76),
-        ("~Qr%^-+++$leM", "r'qC0$sss;Ahf", 8, 90),
-        (
-            "The quick brown fox jumps over the lazy dog",
-            "K7: .*6<4 =-0(1 90' 5*2/, 0):- +7: 3>%& ;08",
-            94,
-            0,
-        ),
-        (
-            "One-1, Two-2, Three-3, Four-4, Five-5, Six-6, Seven-7, Eight-8, Nine-9, Ten-10",
-            "H&60>\\2*uY0q\\2*p4660E\\2XYn40x\\2XDB60L\\2VDI0 \\2V6B6&0S\\2%D=p;0'\\2tD&60Z\\2*6&0>j",
-            51,
-            18,
-        ),
-    ];
-
-    for (plaintext, expected_ciphertext, a, b) in test_cases {
-        let cipher = AffineCipher::new(a, b);
-        let ciphertext = cipher.encrypt(plaintext);
-        assert_eq!(ciphertext, expected_ciphertext);
-
-        let decrypted_plaintext = cipher.decrypt(&ciphertext);
-        assert_eq!(decrypted_plaintext, Ok(plaintext.to_string()));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn modular_inverse_known_answers() {
+        assert_eq!(modular_inverse(7, 95), Ok(68));
+        assert_eq!(modular_inverse(94, 95), Ok(94));
+        assert_eq!(modular_inverse(1, 95), Ok(1));
+        assert_eq!(modular_inverse(5, 95), Err("Inverse does not exist"));
+        assert_eq!(modular_inverse(19, 95), Err("Inverse does not exist"));
     }
 
-    println!("All tests have successfully passed!");
-}
+    #[test]
+    fn known_answers() {
+        let test_cases = [
+            ("Hello!", "&3ddy2", 7, 11),
+            ("TheAlgorithms/C", "DNC}=jHS2zN!7;E", 67, 67),
+            ("0123456789", "840,($ {ws", 91, 88),
+            ("7W@;cdeRT9uL", "JDfa*we?z&bL", 77, 76),
+            ("~Qr%^-+++$leM", "r'qC0$sss;Ahf", 8, 90),
+            (
+                "The quick brown fox jumps over the lazy dog",
+                "K7: .*6<4 =-0(1 90' 5*2/, 0):- +7: 3>%& ;08",
+                94,
+                0,
+            ),
+            (
+                "One-1, Two-2, Three-3, Four-4, Five-5, Six-6, Seven-7, Eight-8, Nine-9, Ten-10",
+                "H&60>\\2*uY0q\\2*p4660E\\2XYn40x\\2XDB60L\\2VDI0 \\2V6B6&0S\\2%D=p;0'\\2tD&60Z\\2*6&0>j",
+                51,
+                18,
+            ),
+        ];
+
+        for (plaintext, expected_ciphertext, a, b) in test_cases {
+            let cipher = AffineCipher::new(a, b).unwrap();
+            assert_eq!(cipher.encrypt(plaintext), expected_ciphertext);
+            assert_eq!(cipher.decrypt(expected_ciphertext), plaintext);
+        }
+    }
 
-fn main() {
-    test_affine();
+    #[test]
+    fn non_invertible_key_rejected() {
+        for a in [0, 5, 19, 95, -5] {
+            assert_eq!(
+                AffineCipher::new(a, 3).err(),
+                Some("Inverse does not exist"),
+                "a {}",
+                a
+            );
+        }
+    }
+
+    #[test]
+    fn keys_are_taken_modulo_alphabet_size() {
+        let cipher = AffineCipher::new(7, 11).unwrap();
+        for (a, b) in [(7 + 95, 11), (7 - 95, 11), (7, 11 - 95), (7, 11 + 190)] {
+            let equivalent = AffineCipher::new(a, b).unwrap();
+            assert_eq!(
+                equivalent.encrypt("Hello!"),
+                cipher.encrypt("Hello!"),
+                "a {} b {}",
+                a,
+                b
+            );
+        }
+    }
+
+    #[test]
+    fn non_printable_passes_through() {
+        let cipher = AffineCipher::new(7, 11).unwrap();
+        assert_eq!(cipher.encrypt("H\té\nello!"), "&\té\n3ddy2");
+        assert_eq!(cipher.decrypt("&\té\n3ddy2"), "H\té\nello!");
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..500 {
+            // Only keys coprime with the alphabet size can be inverted
+            let a = loop {
+                let a = rng.gen_range(1..ALPHABET_SIZE as i32);
+                if modular_inverse(a, ALPHABET_SIZE as i32).is_ok() {
+                    break a;
+                }
+            };
+            let cipher = AffineCipher::new(a, rng.gen_range(-200..200)).unwrap();
+            let plaintext = crate::random_text(&mut rng);
+
+            let ciphertext = cipher.encrypt(&plaintext);
+            assert_eq!(ciphertext.len(), plaintext.len());
+            assert_eq!(cipher.decrypt(&ciphertext), plaintext);
+        }
+    }
 }
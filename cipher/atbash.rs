@@ -1,17 +1,20 @@
-struct AtbashCipher;
+#[derive(Default)]
+pub struct AtbashCipher;
 
 impl AtbashCipher {
-    fn new() -> AtbashCipher {
+    pub fn new() -> AtbashCipher {
         AtbashCipher
     }
 
-    fn encrypt(&self, plaintext: &str) -> String {
+    pub fn encrypt(&self, plaintext: &str) -> String {
         let mut ciphertext = String::new();
         for c in plaintext.chars() {
+            // Test the char itself; 'c as u8' would also match non-ASCII
+            // chars whose low byte happens to be a letter
             let byte = c as u8;
-            if byte >= b'A' && byte <= b'Z' {
+            if c.is_ascii_uppercase() {
                 ciphertext.push(char::from(b'Z' - (byte - b'A')));
-            } else if byte >= b'a' && byte <= b'z' {
+            } else if c.is_ascii_lowercase() {
                 ciphertext.push(char::from(b'z' - (byte - b'a')));
             } else {
                 ciphertext.push(c);
@@ -19,29 +22,54 @@ impl AtbashCipher {
         }
         ciphertext
     }
-}
 
-fn test_atbash_cipher() {
-    let cipher = AtbashCipher::new();
-    let test_cases = vec![
-        ("ABCDEFGHIJKLMNOPQRSTUVWXYZ", "ZYXWVUTSRQPONMLKJIHGFEDCBA"),
-        ("abcdefghijklmnopqrstuvwxyz", "zyxwvutsrqponmlkjihgfedcba"),
-        ("1234567890", "1234567890"),
-        ("HELLO WORLD", "SVOOL DLIOW"),
-        ("Atbash Cipher", "Zgyzhs Xrksvi"),
-    ];
-
-    for (i, (plaintext, expected_ciphertext)) in test_cases.iter().enumerate() {
-        let actual_ciphertext = cipher.encrypt(plaintext);
-        let status = if expected_ciphertext == &actual_ciphertext {
-            "PASSED"
-        } else {
-            "FAILED"
-        };
-        println!("Test {}: {}", i + 1, status);
+    // Atbash is its own inverse
+    pub fn decrypt(&self, ciphertext: &str) -> String {
+        self.encrypt(ciphertext)
     }
 }
 
-fn main() {
-    test_atbash_cipher();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn known_answers() {
+        let cipher = AtbashCipher::new();
+        let test_cases = [
+            ("ABCDEFGHIJKLMNOPQRSTUVWXYZ", "ZYXWVUTSRQPONMLKJIHGFEDCBA"),
+            ("abcdefghijklmnopqrstuvwxyz", "zyxwvutsrqponmlkjihgfedcba"),
+            ("1234567890", "1234567890"),
+            ("HELLO WORLD", "SVOOL DLIOW"),
+            ("Atbash Cipher", "Zgyzhs Xrksvi"),
+            ("", ""),
+        ];
+
+        for (plaintext, expected_ciphertext) in test_cases {
+            assert_eq!(cipher.encrypt(plaintext), expected_ciphertext);
+            assert_eq!(cipher.decrypt(expected_ciphertext), plaintext);
+        }
+    }
+
+    #[test]
+    fn non_ascii_passes_through() {
+        let cipher = AtbashCipher::new();
+        // U+0141 and U+0161 have the low bytes of 'A' and 'a'
+        assert_eq!(cipher.encrypt("Łšé Ab"), "Łšé Zy");
+    }
+
+    #[test]
+    fn round_trip() {
+        let cipher = AtbashCipher::new();
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..500 {
+            let text = crate::random_text(&mut rng);
+
+            let ciphertext = cipher.encrypt(&text);
+            assert_eq!(ciphertext.chars().count(), text.chars().count());
+            assert_eq!(cipher.decrypt(&ciphertext), text);
+        }
+    }
 }
@@ -1,21 +1,21 @@
-struct CaesarCipher {
+pub struct CaesarCipher {
     key: i32,
     alphabet_size: u8,
 }
 
 impl CaesarCipher {
-    fn new(key: i32) -> CaesarCipher {
+    pub fn new(key: i32) -> CaesarCipher {
         CaesarCipher {
             key,
             alphabet_size: 26,
         }
     }
 
-    fn encrypt(&self, text: &str) -> String {
+    pub fn encrypt(&self, text: &str) -> String {
         self.process(text, self.key)
     }
 
-    fn decrypt(&self, text: &str) -> String {
+    pub fn decrypt(&self, text: &str) -> String {
         self.process(text, -self.key)
     }
 
@@ -27,7 +27,9 @@ impl CaesarCipher {
 
             if is_upper_case || is_lower_case {
                 let base = if is_upper_case { b'A' } else { b'a' };
-                let index = ((c as u8 - base) as i32 + key + self.alphabet_size as i32) % self.alphabet_size as i32;
+                // rem_euclid keeps the index in range for negative keys and
+                // keys beyond the alphabet size
+                let index = ((c as u8 - base) as i32 + key).rem_euclid(self.alphabet_size as i32);
                 let processed_char = (base + index as u8) as char;
                 result.push(processed_char);
             } else {
@@ -38,36 +40,44 @@ impl CaesarCipher {
     }
 }
 
-fn test_caesar() {
-    let test_cases = vec![
-        (1, "Hello World!", "Ifmmp Xpsme!"),
-        (3, "ABCDEFGHIJKLMNOPQRSTUVWXYZ", "DEFGHIJKLMNOPQRSTUVWXYZABC"),
-        (5, "The Quick Brown Fox", "Ymj Vznhp Gwtbs Ktc"),
-        (13, "Caesar Cipher", "Pnrfne Pvcure"),
-    ];
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
 
-    for (i, (key, text, encrypted)) in test_cases.iter().enumerate() {
-        let caesar_cipher = CaesarCipher::new(*key);
-        let encrypted_text = caesar_cipher.encrypt(text);
-        let decrypted_text = caesar_cipher.decrypt(&encrypted_text);
+    #[test]
+    fn known_answers() {
+        let test_cases = [
+            (1, "Hello World!", "Ifmmp Xpsme!"),
+            (3, "ABCDEFGHIJKLMNOPQRSTUVWXYZ", "DEFGHIJKLMNOPQRSTUVWXYZABC"),
+            (
+                3,
+                "THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG",
+                "WKH TXLFN EURZQ IRA MXPSV RYHU WKH ODCB GRJ",
+            ),
+            (5, "The Quick Brown Fox", "Ymj Vznhp Gwtbs Ktc"),
+            (13, "Caesar Cipher", "Pnrfne Pvcure"),
+            (-3, "Defend the east wall", "Abcbka qeb bxpq txii"),
+            (29, "xyz", "abc"),
+            (26, "Unchanged", "Unchanged"),
+        ];
 
-        println!("Test Case {}:", i + 1);
-        println!("  Key: {}", key);
-        println!("  Original Text: {}", text);
-        println!("  Encrypted Text: {}", encrypted_text);
-        println!("  Decrypted Text: {}", decrypted_text);
+        for (key, text, encrypted) in test_cases {
+            let cipher = CaesarCipher::new(key);
+            assert_eq!(cipher.encrypt(text), encrypted, "key {}", key);
+            assert_eq!(cipher.decrypt(encrypted), text, "key {}", key);
+        }
+    }
 
-        let encryption_success = encrypted_text == *encrypted;
-        let decryption_success = decrypted_text == *text;
+    #[test]
+    fn round_trip() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..500 {
+            let cipher = CaesarCipher::new(rng.gen_range(-100..100));
+            let text = crate::random_text(&mut rng);
 
-        if encryption_success && decryption_success {
-            println!("  Result: PASSED\n");
-        } else {
-            println!("  Result: FAILED\n");
+            assert_eq!(cipher.decrypt(&cipher.encrypt(&text)), text, "key {}", cipher.key);
         }
     }
 }
-
-fn main() {
-    test_caesar();
-}
@@ -0,0 +1,76 @@
+#[derive(Default)]
+pub struct AnagramChecker;
+
+impl AnagramChecker {
+    pub fn new() -> AnagramChecker {
+        AnagramChecker
+    }
+
+    fn calculate_frequency(&self, input: &str) -> [i32; 26] {
+        let mut freq = [0; 26];
+
+        for c in input.chars().filter(|c| c.is_ascii_alphabetic()) {
+            let index = (c.to_ascii_lowercase() as u8 - b'a') as usize;
+            freq[index] += 1;
+        }
+
+        freq
+    }
+
+    pub fn check(&self, a: &str, b: &str) -> bool {
+        let freq_a = self.calculate_frequency(a);
+        let freq_b = self.calculate_frequency(b);
+
+        freq_a == freq_b
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn known_answers() {
+        let anagram_checker = AnagramChecker::new();
+        let test_cases = [
+            ("listen", "silent", true),
+            ("hello", "world", false),
+            ("cinema", "iceman", true),
+            ("rat", "car", false),
+            ("abc", "def", false),
+            ("aab", "bba", false),
+            ("aabbcc", "abcabc", true),
+            ("", "", true), // Empty strings are anagrams
+            ("Listen", "Silent", true),
+            ("Dormitory", "dirty room", true),
+            ("The eyes", "They see!", true),
+            ("Astronomer", "Moon starer", true),
+            ("abc", "abcc", false),
+        ];
+
+        for (a, b, expected) in test_cases {
+            assert_eq!(anagram_checker.check(a, b), expected, "'{}' and '{}'", a, b);
+        }
+    }
+
+    #[test]
+    fn shuffled_text_is_an_anagram() {
+        let anagram_checker = AnagramChecker::new();
+        let mut rng = StdRng::seed_from_u64(4);
+        for _ in 0..500 {
+            let len = rng.gen_range(0..64);
+            let text: Vec<char> = (0..len).map(|_| rng.gen_range(' '..='~')).collect();
+            let mut shuffled = text.clone();
+            shuffled.shuffle(&mut rng);
+
+            let text: String = text.into_iter().collect();
+            let shuffled: String = shuffled.into_iter().collect();
+            assert!(anagram_checker.check(&text, &shuffled));
+            assert!(anagram_checker.check(&text, &shuffled.to_uppercase()));
+            assert!(!anagram_checker.check(&text, &(shuffled + "q")));
+        }
+    }
+}
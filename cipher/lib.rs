@@ -0,0 +1,35 @@
+// Classical ciphers. Every cipher is a cargo feature, all on by default.
+
+#[cfg(feature = "affine")]
+pub mod affine;
+#[cfg(feature = "atbash")]
+pub mod atbash;
+#[cfg(feature = "caesar")]
+pub mod caesar;
+#[cfg(feature = "check_anagram")]
+pub mod check_anagram;
+#[cfg(feature = "playfair")]
+pub mod playfair;
+#[cfg(feature = "rail_fence")]
+pub mod rail_fence;
+#[cfg(feature = "rot13")]
+pub mod rot13;
+#[cfg(feature = "vigenere")]
+pub mod vigenere;
+
+// Up to 63 characters, mostly printable ASCII with some arbitrary Unicode,
+// for the round-trip tests
+#[cfg(test)]
+#[allow(dead_code)]
+pub(crate) fn random_text(rng: &mut impl rand::Rng) -> String {
+    let len = rng.gen_range(0..64);
+    (0..len)
+        .map(|_| {
+            if rng.gen_bool(0.8) {
+                rng.gen_range(' '..='~')
+            } else {
+                rng.gen::<char>()
+            }
+        })
+        .collect()
+}
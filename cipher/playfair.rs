@@ -1,13 +1,12 @@
-struct PlayfairCipher {
-    key: String,
+pub struct PlayfairCipher {
     matrix: Vec<Vec<char>>,
 }
 
 impl PlayfairCipher {
-    fn new(key: &str) -> PlayfairCipher {
+    pub fn new(key: &str) -> PlayfairCipher {
         let key = key.to_uppercase().replace("J", "I");
         let matrix = PlayfairCipher::generate_matrix(&key);
-        PlayfairCipher { key, matrix }
+        PlayfairCipher { matrix }
     }
 
     fn generate_matrix(key: &str) -> Vec<Vec<char>> {
@@ -29,7 +28,8 @@ impl PlayfairCipher {
             }
         }
 
-        for c in key.chars() {
+        // Spaces, digits and punctuation in the key are ignored
+        for c in key.chars().filter(|c| c.is_ascii_alphabetic()) {
             add_char(c, &mut seen, &mut row, &mut matrix);
         }
 
@@ -40,68 +40,150 @@ impl PlayfairCipher {
         matrix
     }
 
-    fn encrypt(&self, plaintext: &str) -> String {
-        let plaintext = plaintext
+    // Upper-cases, maps J to I and drops everything but letters, then splits
+    // into digraphs: a doubled letter gets a filler X (Q after an X) between
+    // them, and an odd final letter is padded the same way
+    fn prepare(text: &str) -> Vec<(char, char)> {
+        let letters: Vec<char> = text
             .to_uppercase()
             .replace("J", "I")
             .chars()
             .filter(|c| c.is_ascii_alphabetic())
-            .collect::<String>();
-        let mut ciphertext = String::new();
-
-        fn find_position(matrix: &Vec<Vec<char>>, c: char) -> (usize, usize) {
-            for (row, row_vec) in matrix.iter().enumerate() {
-                for (col, &ch) in row_vec.iter().enumerate() {
-                    if ch == c {
-                        return (row, col);
-                    }
+            .collect();
+
+        let mut pairs = Vec::with_capacity(letters.len() / 2 + 1);
+        let mut i = 0;
+        while i < letters.len() {
+            let a = letters[i];
+            let filler = if a == 'X' { 'Q' } else { 'X' };
+            match letters.get(i + 1) {
+                Some(&b) if b != a => {
+                    pairs.push((a, b));
+                    i += 2;
+                }
+                _ => {
+                    pairs.push((a, filler));
+                    i += 1;
                 }
             }
-            (0, 0) // Fallback, should not happen with valid input
         }
+        pairs
+    }
 
-        fn encrypt_pair(matrix: &Vec<Vec<char>>, a: char, b: char) -> String {
-            let (mut row1, mut col1) = find_position(matrix, a);
-            let (mut row2, mut col2) = find_position(matrix, b);
-
-            if row1 == row2 {
-                col1 = (col1 + 1) % 5;
-                col2 = (col2 + 1) % 5;
-            } else if col1 == col2 {
-                row1 = (row1 + 1) % 5;
-                row2 = (row2 + 1) % 5;
-            } else {
-                let temp = col1;
-                col1 = col2;
-                col2 = temp;
+    fn find_position(&self, c: char) -> (usize, usize) {
+        for (row, row_vec) in self.matrix.iter().enumerate() {
+            for (col, &ch) in row_vec.iter().enumerate() {
+                if ch == c {
+                    return (row, col);
+                }
             }
+        }
+        (0, 0) // Fallback, should not happen with valid input
+    }
 
-            format!("{}{}", matrix[row1][col1], matrix[row2][col2])
+    // Moves both letters 'shift' places along their shared row or column
+    // (1 to encrypt, 4 to decrypt), or swaps their columns otherwise
+    fn process_pair(&self, a: char, b: char, shift: usize) -> [char; 2] {
+        let (mut row1, mut col1) = self.find_position(a);
+        let (mut row2, mut col2) = self.find_position(b);
+
+        if row1 == row2 {
+            col1 = (col1 + shift) % 5;
+            col2 = (col2 + shift) % 5;
+        } else if col1 == col2 {
+            row1 = (row1 + shift) % 5;
+            row2 = (row2 + shift) % 5;
+        } else {
+            std::mem::swap(&mut col1, &mut col2);
         }
 
-        let mut i = 0;
-        while i < plaintext.len() {
-            let a = plaintext.chars().nth(i).unwrap();
-            let b = if i + 1 < plaintext.len() {
-                plaintext.chars().nth(i + 1).unwrap()
-            } else {
-                'X'
-            };
-
-            let b = if a == b { 'X' } else { b };
-
-            ciphertext.push_str(&encrypt_pair(&self.matrix, a, b));
-            i += 2;
+        [self.matrix[row1][col1], self.matrix[row2][col2]]
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> String {
+        PlayfairCipher::prepare(plaintext)
+            .into_iter()
+            .flat_map(|(a, b)| self.process_pair(a, b, 1))
+            .collect()
+    }
+
+    // Returns the prepared plaintext, filler letters included
+    pub fn decrypt(&self, ciphertext: &str) -> Result<String, &'static str> {
+        let letters: Vec<char> = ciphertext
+            .to_uppercase()
+            .replace("J", "I")
+            .chars()
+            .filter(|c| c.is_ascii_alphabetic())
+            .collect();
+        if !letters.len().is_multiple_of(2) {
+            return Err("Ciphertext must have an even number of letters");
         }
 
-        ciphertext
+        Ok(letters
+            .chunks(2)
+            .flat_map(|pair| self.process_pair(pair[0], pair[1], 4))
+            .collect())
     }
 }
 
-fn main() {
-    let cipher = PlayfairCipher::new("KEY");
-    assert_eq!(cipher.encrypt("HELLO WORLD"), "DBNVMZMTQL");
-    assert_eq!(cipher.encrypt("PLAYFAIR CIPHER"), "QIBAGYMPIPTCYQ");
-    assert_eq!(cipher.encrypt("ABCD"), "BKDF");
-    println!("All tests PASSED");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn known_answers() {
+        let cipher = PlayfairCipher::new("KEY");
+        assert_eq!(cipher.encrypt("PLAYFAIR CIPHER"), "QIBAGYMPIPTCYQ");
+        assert_eq!(cipher.encrypt("ABCD"), "BKDF");
+        // HE LX LO WO RL DX: the doubled L gets a filler instead of being lost
+        assert_eq!(cipher.encrypt("HELLO WORLD"), "DBNVMIZMQMGV");
+        assert_eq!(cipher.decrypt("DBNVMIZMQMGV"), Ok("HELXLOWORLDX".to_string()));
+
+        // Example from the Wikipedia article on the Playfair cipher
+        let cipher = PlayfairCipher::new("playfair example");
+        assert_eq!(cipher.encrypt("Hide the gold in the tree stump"), "BMODZBXDNABEKUDMUIXMMOUVIF");
+        assert_eq!(
+            cipher.decrypt("BMODZBXDNABEKUDMUIXMMOUVIF"),
+            Ok("HIDETHEGOLDINTHETREXESTUMP".to_string())
+        );
+    }
+
+    #[test]
+    fn prepare_inserts_fillers() {
+        let pairs = |text| -> String {
+            PlayfairCipher::prepare(text).into_iter().flat_map(|(a, b)| [a, b]).collect()
+        };
+        assert_eq!(pairs("balloon"), "BALXLOON");
+        assert_eq!(pairs("jazz"), "IAZXZX");
+        assert_eq!(pairs("xx"), "XQXQ");
+        assert_eq!(pairs("a1 b!"), "AB");
+        assert_eq!(pairs(""), "");
+    }
+
+    #[test]
+    fn odd_ciphertext_is_rejected() {
+        let cipher = PlayfairCipher::new("KEY");
+        assert!(cipher.decrypt("ABC").is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..500 {
+            let key: String = (0..rng.gen_range(0..12)).map(|_| rng.gen_range(' '..='~')).collect();
+            let cipher = PlayfairCipher::new(&key);
+            let len = rng.gen_range(0..64);
+            let text: String = (0..len).map(|_| rng.gen_range(' '..='~')).collect();
+
+            let ciphertext = cipher.encrypt(&text);
+            let prepared: String = PlayfairCipher::prepare(&text)
+                .into_iter()
+                .flat_map(|(a, b)| [a, b])
+                .collect();
+            assert_eq!(ciphertext.len(), prepared.len());
+            assert_eq!(cipher.decrypt(&ciphertext), Ok(prepared), "key {:?}, text {:?}", key, text);
+        }
+    }
 }
@@ -0,0 +1,121 @@
+pub struct RailFenceCipher {
+    key: usize,
+}
+
+impl RailFenceCipher {
+    pub fn new(key: usize) -> Result<RailFenceCipher, &'static str> {
+        if key == 0 {
+            return Err("Rail fence needs at least one rail");
+        }
+        Ok(RailFenceCipher { key })
+    }
+
+    fn create_rail_matrix(&self, text: &str) -> Vec<Vec<Option<char>>> {
+        let mut rail = vec![vec![None; text.len()]; self.key];
+
+        let mut dir_down = false;
+        let mut row = 0;
+
+        for (col, c) in text.chars().enumerate() {
+            rail[row][col] = Some(c);
+
+            // A single rail has nowhere to zigzag to
+            if self.key == 1 {
+                continue;
+            }
+
+            if row == 0 || row == self.key - 1 {
+                dir_down = !dir_down;
+            }
+
+            if dir_down {
+                row += 1;
+            } else {
+                row -= 1;
+            }
+        }
+
+        rail
+    }
+
+    pub fn encrypt(&self, text: &str) -> String {
+        let rail = self.create_rail_matrix(text);
+        let mut result = String::new();
+
+        for rail_row in &rail {
+            for &c in rail_row.iter().flatten() {
+                result.push(c);
+            }
+        }
+
+        result
+    }
+
+    pub fn decrypt(&self, cipher: &str) -> String {
+        let mut rail = self.create_rail_matrix(cipher);
+        let mut chars = cipher.chars();
+
+        for rail_row in rail.iter_mut() {
+            for cell in rail_row.iter_mut() {
+                if cell.is_some() {
+                    *cell = chars.next();
+                }
+            }
+        }
+
+        // Each column holds exactly one character; read them off in order
+        (0..cipher.len())
+            .filter_map(|col| rail.iter().find_map(|rail_row| rail_row[col]))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn known_answers() {
+        let test_cases = [
+            // Example from the Wikipedia article on the rail fence cipher
+            ("WEAREDISCOVEREDFLEEATONCE", 3, "WECRLTEERDSOEEFEAOCAIVDEN"),
+            ("Hello World!", 2, "HloWrdel ol!"),
+            ("Hello World!", 3, "Horel ol!lWd"),
+            ("Rail Fence Cipher", 1, "Rail Fence Cipher"),
+            ("abc", 5, "abc"),
+            ("", 3, ""),
+        ];
+
+        for (text, key, encrypted) in test_cases {
+            let cipher = RailFenceCipher::new(key).unwrap();
+            assert_eq!(cipher.encrypt(text), encrypted, "key {}", key);
+            assert_eq!(cipher.decrypt(encrypted), text, "key {}", key);
+        }
+    }
+
+    #[test]
+    fn zero_rails_rejected() {
+        assert!(RailFenceCipher::new(0).is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut rng = StdRng::seed_from_u64(6);
+        for _ in 0..500 {
+            let key = rng.gen_range(1..12);
+            let cipher = RailFenceCipher::new(key).unwrap();
+            let text = crate::random_text(&mut rng);
+
+            let ciphertext = cipher.encrypt(&text);
+            let mut sorted_text: Vec<char> = text.chars().collect();
+            let mut sorted_ciphertext: Vec<char> = ciphertext.chars().collect();
+            sorted_text.sort_unstable();
+            sorted_ciphertext.sort_unstable();
+            // A transposition: same characters, new order
+            assert_eq!(sorted_ciphertext, sorted_text);
+            assert_eq!(cipher.decrypt(&ciphertext), text, "key {}", key);
+        }
+    }
+}
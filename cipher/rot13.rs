@@ -1,22 +1,30 @@
-struct Rot13Cipher;
+#[derive(Default)]
+pub struct Rot13Cipher;
 
 impl Rot13Cipher {
-    fn new() -> Rot13Cipher {
+    pub fn new() -> Rot13Cipher {
         Rot13Cipher
     }
 
-    fn apply(&self, s: &str) -> String {
+    pub fn apply(&self, s: &str) -> String {
         let mut result = String::new();
 
         for char in s.chars() {
+            // Only ASCII letters rotate; anything else, including non-ASCII
+            // chars, is copied unchanged rather than truncated to a byte
+            if !char.is_ascii_alphabetic() {
+                result.push(char);
+                continue;
+            }
+
             let mut byte = char as u8;
-            
-            if byte >= b'A' && byte <= b'Z' {
+
+            if byte.is_ascii_uppercase() {
                 byte = b'A' + (byte - b'A' + 13) % 26;
-            } else if byte >= b'a' && byte <= b'z' {
+            } else {
                 byte = b'a' + (byte - b'a' + 13) % 26;
             }
-            
+
             result.push(char::from(byte));
         }
 
@@ -24,22 +32,39 @@ impl Rot13Cipher {
     }
 }
 
-fn test_rot13() {
-    let cipher = Rot13Cipher::new();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn known_answers() {
+        let cipher = Rot13Cipher::new();
 
-    let test_cases = vec![
-        ("The more I C, the less I see.", "Gur zber V P, gur yrff V frr."),
-        ("Which witch switched the Swiss wristwatches?", "Juvpu jvgpu fjvgpurq gur Fjvff jevfgjngpurf?"),
-        ("Juvpu jvgpu fjvgpurq gur Fjvff jevfgjngpurf?", "Which witch switched the Swiss wristwatches?"),
-    ];
+        let test_cases = [
+            ("The more I C, the less I see.", "Gur zber V P, gur yrff V frr."),
+            ("Which witch switched the Swiss wristwatches?", "Juvpu jvgpu fjvgpurq gur Fjvff jevfgjngpurf?"),
+            ("Juvpu jvgpu fjvgpurq gur Fjvff jevfgjngpurf?", "Which witch switched the Swiss wristwatches?"),
+            ("ABCDEFGHIJKLMNOPQRSTUVWXYZ", "NOPQRSTUVWXYZABCDEFGHIJKLM"),
+            ("Hello, 世界 €5", "Uryyb, 世界 €5"),
+            ("", ""),
+        ];
 
-    for (input, expected) in test_cases {
-        assert_eq!(cipher.apply(input), expected);
+        for (input, expected) in test_cases {
+            assert_eq!(cipher.apply(input), expected);
+        }
     }
 
-    println!("All tests have successfully passed!");
-}
+    #[test]
+    fn round_trip() {
+        let cipher = Rot13Cipher::new();
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..500 {
+            let text = crate::random_text(&mut rng);
 
-fn main() {
-    test_rot13();
+            // ROT13 is its own inverse
+            assert_eq!(cipher.apply(&cipher.apply(&text)), text);
+        }
+    }
 }
@@ -1,22 +1,30 @@
-struct VigenereCipher {
-    key: String,
+pub struct VigenereCipher {
+    // Shift for each key letter, 0 for A through 25 for Z
+    offsets: Vec<u8>,
 }
 
 impl VigenereCipher {
-    fn new(key: &str) -> VigenereCipher {
-        VigenereCipher {
-            key: key.to_uppercase(),
+    // Spaces, digits and punctuation in the key are ignored; a key with no
+    // letters at all is rejected
+    pub fn new(key: &str) -> Result<VigenereCipher, &'static str> {
+        let offsets: Vec<u8> = key
+            .bytes()
+            .filter(u8::is_ascii_alphabetic)
+            .map(|b| b.to_ascii_uppercase() - b'A')
+            .collect();
+        if offsets.is_empty() {
+            return Err("Key must contain at least one letter");
         }
+        Ok(VigenereCipher { offsets })
     }
 
-    fn encrypt(&self, text: &str) -> String {
+    pub fn encrypt(&self, text: &str) -> String {
         let mut encrypted = String::new();
-        let mut key_index = 0;
+        let mut offsets = self.offsets.iter().cycle();
         for c in text.chars() {
             if c.is_ascii_uppercase() {
-                let offset = self.key.chars().nth(key_index).unwrap() as u8 - b'A';
+                let offset = offsets.next().unwrap();
                 encrypted.push((((c as u8 - b'A' + offset) % 26) + b'A') as char);
-                key_index = (key_index + 1) % self.key.len();
             } else {
                 encrypted.push(c);
             }
@@ -24,14 +32,13 @@ impl VigenereCipher {
         encrypted
     }
 
-    fn decrypt(&self, text: &str) -> String {
+    pub fn decrypt(&self, text: &str) -> String {
         let mut decrypted = String::new();
-        let mut key_index = 0;
+        let mut offsets = self.offsets.iter().cycle();
         for c in text.chars() {
             if c.is_ascii_uppercase() {
-                let offset = self.key.chars().nth(key_index).unwrap() as u8 - b'A';
+                let offset = offsets.next().unwrap();
                 decrypted.push((((c as u8 - b'A' + 26 - offset) % 26) + b'A') as char);
-                key_index = (key_index + 1) % self.key.len();
             } else {
                 decrypted.push(c);
             }
@@ -40,19 +47,57 @@ impl VigenereCipher {
     }
 }
 
-fn test_vigenere_cipher() {
-    let cipher = VigenereCipher::new("KEY");
-    let text = "HELLO WORLD";
-    let encrypted = cipher.encrypt(text);
-    let decrypted = cipher.decrypt(&encrypted);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
 
-    assert_ne!(encrypted, text, "Test 1 FAILED");
-    assert_eq!(decrypted, text, "Test 2 FAILED");
-    assert_eq!(cipher.encrypt("ABC"), cipher.encrypt("ABC"), "Test 3 FAILED");
+    #[test]
+    fn known_answers() {
+        let test_cases = [
+            // Example from the Wikipedia article on the Vigenère cipher
+            ("LEMON", "ATTACKATDAWN", "LXFOPVEFRNHR"),
+            ("KEY", "HELLO WORLD", "RIJVS UYVJN"),
+            ("key", "HELLO WORLD", "RIJVS UYVJN"),
+            ("A", "UNCHANGED", "UNCHANGED"),
+            // Only upper-case letters are enciphered or advance the key
+            ("B", "Mixed case 123", "Nixed case 123"),
+        ];
 
-    println!("All tests PASSED");
-}
+        for (key, text, encrypted) in test_cases {
+            let cipher = VigenereCipher::new(key).unwrap();
+            assert_eq!(cipher.encrypt(text), encrypted, "key {}", key);
+            assert_eq!(cipher.decrypt(encrypted), text, "key {}", key);
+        }
+    }
+
+    #[test]
+    fn non_letters_in_key_are_ignored() {
+        let reference = VigenereCipher::new("KEY").unwrap();
+        for key in ["KEY 1", "k-e-y!", "  KEY  "] {
+            let cipher = VigenereCipher::new(key).unwrap();
+            assert_eq!(cipher.encrypt("HELLO WORLD"), reference.encrypt("HELLO WORLD"), "key {:?}", key);
+            assert_eq!(cipher.decrypt("RIJVS UYVJN"), "HELLO WORLD", "key {:?}", key);
+        }
+    }
 
-fn main() {
-    test_vigenere_cipher();
+    #[test]
+    fn key_without_letters_rejected() {
+        for key in ["", " ", "123", "\u{e9}"] {
+            assert!(VigenereCipher::new(key).is_err(), "key {:?}", key);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut rng = StdRng::seed_from_u64(8);
+        for _ in 0..500 {
+            let key: String = (0..rng.gen_range(1..12)).map(|_| rng.gen_range('A'..='Z')).collect();
+            let cipher = VigenereCipher::new(&key).unwrap();
+            let text = crate::random_text(&mut rng);
+
+            assert_eq!(cipher.decrypt(&cipher.encrypt(&text)), text, "key {}", key);
+        }
+    }
 }
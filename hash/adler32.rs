@@ -1,47 +1,302 @@
-struct Adler32Cipher;
+use crate::hasher::Hasher;
+
+const MOD_ADLER: u32 = 65521;
+
+// Largest n such that 255 * n * (n + 1) / 2 + (n + 1) * (MOD_ADLER - 1) fits in a u32,
+// i.e. how many bytes can be summed before 'b' must be reduced
+const NMAX: usize = 5552;
+
+#[derive(Default)]
+pub struct Adler32Cipher;
 
 impl Adler32Cipher {
-    fn new() -> Adler32Cipher {
+    pub fn new() -> Adler32Cipher {
         Adler32Cipher
     }
 
-    fn encrypt(&self, input: &str) -> u32 {
-        const MOD_ADLER: u32 = 65521;
+    pub fn encrypt(&self, input: impl AsRef<[u8]>) -> u32 {
         let mut a: u32 = 1;
         let mut b: u32 = 0;
 
-        for byte in input.bytes() {
+        for &byte in input.as_ref() {
             a = (a + byte as u32) % MOD_ADLER;
             b = (b + a) % MOD_ADLER;
         }
 
         (b << 16) | a
     }
+
+    // Same result as encrypt, but takes the modulo only once per NMAX bytes
+    pub fn encrypt_fast(&self, input: impl AsRef<[u8]>) -> u32 {
+        let (a, b) = adler32_fast_update(1, 0, input.as_ref());
+        (b << 16) | a
+    }
+}
+
+fn adler32_fast_update(mut a: u32, mut b: u32, data: &[u8]) -> (u32, u32) {
+    for chunk in data.chunks(NMAX) {
+        let mut blocks = chunk.chunks_exact(8);
+        for block in &mut blocks {
+            a += block[0] as u32;
+            b += a;
+            a += block[1] as u32;
+            b += a;
+            a += block[2] as u32;
+            b += a;
+            a += block[3] as u32;
+            b += a;
+            a += block[4] as u32;
+            b += a;
+            a += block[5] as u32;
+            b += a;
+            a += block[6] as u32;
+            b += a;
+            a += block[7] as u32;
+            b += a;
+        }
+        for &byte in blocks.remainder() {
+            a += byte as u32;
+            b += a;
+        }
+
+        a %= MOD_ADLER;
+        b %= MOD_ADLER;
+    }
+
+    (a, b)
+}
+
+// Incremental Adler-32 over byte slices
+pub struct Adler32State {
+    a: u32,
+    b: u32,
+}
+
+impl Adler32State {
+    pub fn new() -> Adler32State {
+        Adler32State { a: 1, b: 0 }
+    }
+}
+
+impl Default for Adler32State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for Adler32State {
+    type Output = u32;
+
+    fn update(&mut self, data: &[u8]) {
+        (self.a, self.b) = adler32_fast_update(self.a, self.b, data);
+    }
+
+    fn finalize(&mut self) -> u32 {
+        (self.b << 16) | self.a
+    }
+
+    fn reset(&mut self) {
+        *self = Adler32State::new();
+    }
+}
+
+impl_io_write!(Adler32State);
+
+// Adler-32 over a fixed-size sliding window, updated in O(1) per byte
+// (the rsync weak checksum)
+pub struct RollingAdler32 {
+    a: u32,
+    b: u32,
+    window: u32,
+}
+
+impl RollingAdler32 {
+    pub fn new(window: &[u8]) -> RollingAdler32 {
+        let mut state = Adler32State::new();
+        state.update(window);
+        RollingAdler32 {
+            a: state.a,
+            b: state.b,
+            window: (window.len() as u64 % MOD_ADLER as u64) as u32,
+        }
+    }
+
+    // Slides the window one byte: 'old' leaves at the front, 'new' enters at the back
+    pub fn roll(&mut self, old: u8, new: u8) {
+        let old = old as u32;
+        let new = new as u32;
+
+        self.a = (self.a + MOD_ADLER - old + new) % MOD_ADLER;
+        let removed = (self.window * old) % MOD_ADLER;
+        self.b = (self.b + 2 * MOD_ADLER - removed + self.a - 1) % MOD_ADLER;
+    }
+
+    pub fn hash(&self) -> u32 {
+        (self.b << 16) | self.a
+    }
+}
+
+// Adler-32 of A || B given adler(A), adler(B) and len(B), as in zlib's adler32_combine
+pub fn adler32_combine(adler_a: u32, adler_b: u32, len_b: u64) -> u32 {
+    let base = MOD_ADLER as u64;
+    let rem = len_b % base;
+    let a1 = (adler_a & 0xFFFF) as u64;
+    let b1 = (adler_a >> 16) as u64;
+    let a2 = (adler_b & 0xFFFF) as u64;
+    let b2 = (adler_b >> 16) as u64;
+
+    // a = a1 + a2 - 1, b = b1 + b2 + rem * (a1 - 1)
+    let a = (a1 + a2 + base - 1) % base;
+    let b = (b1 + b2 + rem * a1 + base - rem) % base;
+
+    ((b << 16) | a) as u32
 }
 
-fn test_adler32_cipher() {
-    let cipher = Adler32Cipher::new();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hasher::digest_chunked;
+    use std::io::{self, Cursor};
 
-    let test_cases = [
+    // Reference values from zlib's adler32()
+    const KNOWN_ANSWERS: [(&str, u32); 7] = [
+        ("", 0x00000001),
+        ("a", 0x00620062),
+        ("abc", 0x024D0127),
+        ("message digest", 0x29750586),
+        ("abcdefghijklmnopqrstuvwxyz", 0x90860B20),
         ("Wikipedia", 0x11E60398),
         ("Hello, world!", 0x205E048A),
-        ("", 0x1),
     ];
 
-    for (i, test_case) in test_cases.iter().enumerate() {
-        let output = cipher.encrypt(test_case.0);
-        let status = if output == test_case.1 { "PASSED" } else { "FAILED" };
-        println!(
-            "Test {}: input='{}', expected='{:#010X}', output='{:#010X}' - {}",
-            i + 1,
-            test_case.0,
-            test_case.1,
-            output,
-            status
-        );
+    #[test]
+    fn known_answers() {
+        let cipher = Adler32Cipher::new();
+        for (input, expected) in KNOWN_ANSWERS {
+            assert_eq!(cipher.encrypt(input), expected, "input {:?}", input);
+            assert_eq!(cipher.encrypt_fast(input), expected, "input {:?}", input);
+            assert_eq!(Adler32State::new().digest(input.as_bytes()), expected, "input {:?}", input);
+        }
     }
-}
 
-fn main() {
-    test_adler32_cipher();
+    #[test]
+    fn long_known_answers() {
+        let cipher = Adler32Cipher::new();
+        // Both sums wrap many times, and the 0xFF run crosses NMAX
+        assert_eq!(cipher.encrypt_fast(vec![b'a'; 1_000_000]), 0x15D870F9);
+        assert_eq!(cipher.encrypt_fast([0xFF; 6000]), 0xA49759EA);
+        assert_eq!(cipher.encrypt([0xFF; 6000]), 0xA49759EA);
+    }
+
+    #[test]
+    fn streaming_matches_one_shot() {
+        let cipher = Adler32Cipher::new();
+        let input = "The quick brown fox jumps over the lazy dog";
+
+        for chunk_size in 1..=input.len() {
+            let mut state = Adler32State::new();
+            for chunk in input.as_bytes().chunks(chunk_size) {
+                state.update(chunk);
+            }
+            assert_eq!(state.finalize(), cipher.encrypt(input), "chunk size {}", chunk_size);
+        }
+    }
+
+    #[test]
+    fn combine_at_every_split() {
+        let cipher = Adler32Cipher::new();
+        let input = "The quick brown fox jumps over the lazy dog";
+
+        for split in 0..=input.len() {
+            let (a, b) = input.split_at(split);
+            let combined = adler32_combine(cipher.encrypt(a), cipher.encrypt(b), b.len() as u64);
+            assert_eq!(combined, cipher.encrypt(input), "split at {}", split);
+        }
+    }
+
+    #[test]
+    fn hasher_digest_and_reset() {
+        let input = b"The quick brown fox jumps over the lazy dog";
+        let mut state = Adler32State::new();
+        state.update(b"discarded by reset");
+        assert_eq!(state.digest(input), 0x5BDC0FDA);
+        assert_eq!(digest_chunked(&mut state, input, 5), 0x5BDC0FDA);
+    }
+
+    #[test]
+    fn rolling_window_matches_recomputation() {
+        // High bytes push both sums past the modulus while rolling
+        let data: Vec<u8> = (0..5000u32).map(|i| (i.wrapping_mul(2654435761) >> 24) as u8 | 0x80).collect();
+        for &window in &[1usize, 16, 700, 4096] {
+            let mut rolling = RollingAdler32::new(&data[..window]);
+            for start in 1..=data.len() - window {
+                rolling.roll(data[start - 1], data[start + window - 1]);
+
+                let mut state = Adler32State::new();
+                state.update(&data[start..start + window]);
+                assert_eq!(rolling.hash(), state.finalize(), "window {} at {}", window, start);
+            }
+        }
+    }
+
+    #[test]
+    fn binary_input() {
+        let cipher = Adler32Cipher::new();
+        // Every byte value, so not valid UTF-8
+        let data: Vec<u8> = (0..=255).collect();
+        let expected = 0xADF67F81;
+
+        assert_eq!(cipher.encrypt(&data), expected);
+        assert_eq!(cipher.encrypt_fast(&data[..]), expected);
+
+        let mut state = Adler32State::new();
+        assert_eq!(state.digest_reader(Cursor::new(&data)).unwrap(), expected);
+
+        // Larger than the reader's internal buffer
+        let big: Vec<u8> = (0..200_000u32).map(|i| (i * 7 + i / 256) as u8).collect();
+        let mut state = Adler32State::new();
+        let copied = io::copy(&mut Cursor::new(&big), &mut state).unwrap();
+        assert_eq!(copied, big.len() as u64);
+        assert_eq!(state.finalize(), cipher.encrypt(&big));
+    }
+
+    #[test]
+    fn fast_path_matches_simple_path() {
+        let cipher = Adler32Cipher::new();
+
+        // Randomized comparison against the simple path, with lengths straddling
+        // NMAX and all-0xFF runs that maximise the deferred sums
+        let mut state = 0x2545F491u32;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        };
+
+        for round in 0..200 {
+            let len = match round % 4 {
+                0 => (next() % 64) as usize,
+                1 => NMAX - 8 + (next() % 16) as usize,
+                _ => (next() % 40_000) as usize,
+            };
+            let data: Vec<u8> = if round % 10 == 0 {
+                vec![0xFF; len]
+            } else {
+                (0..len).map(|_| next() as u8).collect()
+            };
+
+            let mut a = 1u32;
+            let mut b = 0u32;
+            for &byte in &data {
+                a = (a + byte as u32) % MOD_ADLER;
+                b = (b + a) % MOD_ADLER;
+            }
+
+            let mut fast = Adler32State::new();
+            fast.update(&data);
+            assert_eq!(fast.finalize(), (b << 16) | a, "round {}", round);
+            assert_eq!(cipher.encrypt_fast(&data), cipher.encrypt(&data), "round {}", round);
+        }
+    }
 }
@@ -1,16 +1,15 @@
-use std::convert::TryInto;
+use crate::hasher::Hasher;
+use std::marker::PhantomData;
+use std::ops::{BitXor, BitXorAssign};
 
 const BLOCK_SIZE: usize = 128;
-const WORD_SIZE: usize = 8;
-const DIGEST_SIZE: usize = 64;
-const KEY_SIZE: usize = 64;
-const SALT_SIZE: usize = 16;
-const PERSONAL_SIZE: usize = 16;
-
-const MASK8BITS: u8 = 0xff;
-const MASK16BITS: u16 = 0xffff;
+pub const DIGEST_SIZE: usize = 64;
+pub const KEY_SIZE: usize = 64;
+pub const SALT_SIZE: usize = 16;
+pub const PERSONAL_SIZE: usize = 16;
+const ROUNDS: usize = 12;
+
 const MASK32BITS: u32 = 0xffffffff;
-const MASK48BITS: u64 = 0xffffffffffff;
 const MASK64BITS: u64 = 0xffffffffffffffff;
 
 const ROT1: u32 = 32;
@@ -18,228 +17,1328 @@ const ROT2: u32 = 24;
 const ROT3: u32 = 16;
 const ROT4: u32 = 63;
 
-struct BLAKE2b {
-    h: [u64; 8],
-    t: [u64; 2],
-    f: [u64; 2],
-    buflen: usize,
-    buf: [u8; BLOCK_SIZE],
-    key: Option<[u8; KEY_SIZE]>,
-    finalized: bool,
-}
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+// BLAKE2s: 32-bit words, 64-byte blocks, 10 rounds
+const BLAKE2S_BLOCK_SIZE: usize = 64;
+pub const BLAKE2S_DIGEST_SIZE: usize = 32;
+pub const BLAKE2S_KEY_SIZE: usize = 32;
+pub const BLAKE2S_SALT_SIZE: usize = 8;
+pub const BLAKE2S_PERSONAL_SIZE: usize = 8;
+const BLAKE2S_ROUNDS: usize = 10;
+
+const BLAKE2S_ROT1: u32 = 16;
+const BLAKE2S_ROT2: u32 = 12;
+const BLAKE2S_ROT3: u32 = 8;
+const BLAKE2S_ROT4: u32 = 7;
+
+const BLAKE2S_IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
 
-// Constants for the mixing function 'G' in 12 rounds
-const SIGMA: [[usize; 16]; 12] = [
+// Message word permutations, one row per round (rounds 10 and 11 reuse rows 0 and 1)
+const SIGMA: [[usize; 16]; 10] = [
     [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
     [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
-    // Add more SIGMA rounds as needed
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
 ];
 
-impl BLAKE2b {
-    fn new(digest_size: usize, key: Option<[u8; KEY_SIZE]>) -> Result<Self, &'static str> {
-        if digest_size <= 0 || digest_size > DIGEST_SIZE {
+// Everything that depends on the word size: BLAKE2b works on u64 words,
+// BLAKE2s on u32 words with a half-size block and fewer rounds
+pub trait BLAKE2Word: Copy + PartialOrd + BitXor<Output = Self> + BitXorAssign {
+    const BLOCK_SIZE: usize;
+    const ROUNDS: usize;
+    const ROTATIONS: [u32; 4];
+    const DIGEST_SIZE: usize;
+    const KEY_SIZE: usize;
+    const SALT_SIZE: usize;
+    const PERSONAL_SIZE: usize;
+    // Widths of the node offset and BLAKE2X output length fields
+    const NODE_OFFSET_SIZE: usize;
+    const XOF_LENGTH_SIZE: usize;
+    const IV: [Self; 8];
+    const ZERO: Self;
+    // Value of the finalization flags once set
+    const FLAG: Self;
+
+    fn from_le_slice(bytes: &[u8]) -> Self;
+    fn from_usize(n: usize) -> Self;
+    fn wrapping_add(self, other: Self) -> Self;
+    fn rotate_right(self, n: u32) -> Self;
+    // Byte 'i' of the word in little-endian order
+    fn le_byte(self, i: usize) -> u8;
+}
+
+impl BLAKE2Word for u64 {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+    const ROUNDS: usize = ROUNDS;
+    const ROTATIONS: [u32; 4] = [ROT1, ROT2, ROT3, ROT4];
+    const DIGEST_SIZE: usize = DIGEST_SIZE;
+    const KEY_SIZE: usize = KEY_SIZE;
+    const SALT_SIZE: usize = SALT_SIZE;
+    const PERSONAL_SIZE: usize = PERSONAL_SIZE;
+    const NODE_OFFSET_SIZE: usize = 8;
+    const XOF_LENGTH_SIZE: usize = 4;
+    const IV: [u64; 8] = IV;
+    const ZERO: u64 = 0;
+    const FLAG: u64 = MASK64BITS;
+
+    fn from_le_slice(bytes: &[u8]) -> u64 {
+        u64::from_le_bytes(bytes.try_into().unwrap())
+    }
+
+    fn from_usize(n: usize) -> u64 {
+        n as u64
+    }
+
+    fn wrapping_add(self, other: u64) -> u64 {
+        u64::wrapping_add(self, other)
+    }
+
+    fn rotate_right(self, n: u32) -> u64 {
+        u64::rotate_right(self, n)
+    }
+
+    fn le_byte(self, i: usize) -> u8 {
+        (self >> (8 * i)) as u8
+    }
+}
+
+impl BLAKE2Word for u32 {
+    const BLOCK_SIZE: usize = BLAKE2S_BLOCK_SIZE;
+    const ROUNDS: usize = BLAKE2S_ROUNDS;
+    const ROTATIONS: [u32; 4] = [BLAKE2S_ROT1, BLAKE2S_ROT2, BLAKE2S_ROT3, BLAKE2S_ROT4];
+    const DIGEST_SIZE: usize = BLAKE2S_DIGEST_SIZE;
+    const KEY_SIZE: usize = BLAKE2S_KEY_SIZE;
+    const SALT_SIZE: usize = BLAKE2S_SALT_SIZE;
+    const PERSONAL_SIZE: usize = BLAKE2S_PERSONAL_SIZE;
+    const NODE_OFFSET_SIZE: usize = 6;
+    const XOF_LENGTH_SIZE: usize = 2;
+    const IV: [u32; 8] = BLAKE2S_IV;
+    const ZERO: u32 = 0;
+    const FLAG: u32 = MASK32BITS;
+
+    fn from_le_slice(bytes: &[u8]) -> u32 {
+        u32::from_le_bytes(bytes.try_into().unwrap())
+    }
+
+    fn from_usize(n: usize) -> u32 {
+        n as u32
+    }
+
+    fn wrapping_add(self, other: u32) -> u32 {
+        u32::wrapping_add(self, other)
+    }
+
+    fn rotate_right(self, n: u32) -> u32 {
+        u32::rotate_right(self, n)
+    }
+
+    fn le_byte(self, i: usize) -> u8 {
+        (self >> (8 * i)) as u8
+    }
+}
+
+// Parameter block builder (RFC 7693, section 2.5)
+#[derive(Clone)]
+pub struct BLAKE2Params<W: BLAKE2Word> {
+    digest_size: usize,
+    key: Vec<u8>,
+    salt: Vec<u8>,
+    personal: Vec<u8>,
+    fanout: u8,
+    depth: u8,
+    leaf_size: u32,
+    node_offset: u64,
+    node_depth: u8,
+    inner_size: usize,
+    xof_length: u32,
+    last_node: bool,
+    word: PhantomData<W>,
+}
+
+pub type BLAKE2bParams = BLAKE2Params<u64>;
+pub type BLAKE2sParams = BLAKE2Params<u32>;
+
+impl<W: BLAKE2Word> BLAKE2Params<W> {
+    pub fn new() -> BLAKE2Params<W> {
+        BLAKE2Params {
+            digest_size: W::DIGEST_SIZE,
+            key: Vec::new(),
+            salt: Vec::new(),
+            personal: Vec::new(),
+            fanout: 1,
+            depth: 1,
+            leaf_size: 0,
+            node_offset: 0,
+            node_depth: 0,
+            inner_size: 0,
+            xof_length: 0,
+            last_node: false,
+            word: PhantomData,
+        }
+    }
+
+    pub fn digest_size(mut self, digest_size: usize) -> Self {
+        self.digest_size = digest_size;
+        self
+    }
+
+    pub fn key(mut self, key: &[u8]) -> Self {
+        self.key = key.to_vec();
+        self
+    }
+
+    // Tree parameters: fanout 0 means unlimited, depth 255 means unlimited
+    pub fn fanout(mut self, fanout: u8) -> Self {
+        self.fanout = fanout;
+        self
+    }
+
+    pub fn depth(mut self, depth: u8) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn leaf_size(mut self, leaf_size: u32) -> Self {
+        self.leaf_size = leaf_size;
+        self
+    }
+
+    // At most 48 bits for BLAKE2s
+    pub fn node_offset(mut self, node_offset: u64) -> Self {
+        self.node_offset = node_offset;
+        self
+    }
+
+    pub fn node_depth(mut self, node_depth: u8) -> Self {
+        self.node_depth = node_depth;
+        self
+    }
+
+    pub fn inner_size(mut self, inner_size: usize) -> Self {
+        self.inner_size = inner_size;
+        self
+    }
+
+    // BLAKE2X output length; limits the node offset to 32 bits
+    pub fn xof_length(mut self, xof_length: u32) -> Self {
+        self.xof_length = xof_length;
+        self
+    }
+
+    // Marks the rightmost node of a tree level
+    pub fn last_node(mut self, last_node: bool) -> Self {
+        self.last_node = last_node;
+        self
+    }
+
+    // Salt and personalization shorter than 16 bytes (8 for BLAKE2s) are
+    // zero-padded
+    pub fn salt(mut self, salt: &[u8]) -> Self {
+        self.salt = salt.to_vec();
+        self
+    }
+
+    pub fn personal(mut self, personal: &[u8]) -> Self {
+        self.personal = personal.to_vec();
+        self
+    }
+
+    // The parameter block is eight words long; BLAKE2s uses the first 32 bytes
+    fn to_bytes(&self) -> [u8; 64] {
+        let word_size = std::mem::size_of::<W>();
+        let node_end = 8 + W::NODE_OFFSET_SIZE;
+
+        let mut param_bytes = [0u8; 64];
+        param_bytes[0] = self.digest_size as u8;
+        param_bytes[1] = self.key.len() as u8;
+        param_bytes[2] = self.fanout;
+        param_bytes[3] = self.depth;
+        param_bytes[4..8].copy_from_slice(&self.leaf_size.to_le_bytes());
+        param_bytes[8..node_end]
+            .copy_from_slice(&self.node_offset.to_le_bytes()[..W::NODE_OFFSET_SIZE]);
+        if self.xof_length != 0 {
+            let xof_length = &self.xof_length.to_le_bytes()[..W::XOF_LENGTH_SIZE];
+            param_bytes[12..12 + W::XOF_LENGTH_SIZE].copy_from_slice(xof_length);
+        }
+        param_bytes[node_end] = self.node_depth;
+        param_bytes[node_end + 1] = self.inner_size as u8;
+        let salt = 4 * word_size;
+        param_bytes[salt..salt + self.salt.len()].copy_from_slice(&self.salt);
+        let personal = 6 * word_size;
+        param_bytes[personal..personal + self.personal.len()].copy_from_slice(&self.personal);
+        param_bytes
+    }
+
+    // Validates the parameters and returns the chaining value they give
+    fn initial_state(&self) -> Result<[W; 8], &'static str> {
+        if self.digest_size == 0 || self.digest_size > W::DIGEST_SIZE {
             return Err("Invalid digest size");
         }
+        if self.key.len() > W::KEY_SIZE {
+            return Err("Invalid key size");
+        }
+        if self.salt.len() > W::SALT_SIZE {
+            return Err("Invalid salt size");
+        }
+        if self.personal.len() > W::PERSONAL_SIZE {
+            return Err("Invalid personalization size");
+        }
+        if W::NODE_OFFSET_SIZE < 8 && self.node_offset >> (8 * W::NODE_OFFSET_SIZE) != 0 {
+            return Err("Invalid node offset");
+        }
+        if self.xof_length != 0 && self.node_offset > u32::MAX as u64 {
+            return Err("Invalid node offset");
+        }
+        if W::XOF_LENGTH_SIZE < 4 && self.xof_length >> (8 * W::XOF_LENGTH_SIZE) != 0 {
+            return Err("Invalid output length");
+        }
+        if self.inner_size > W::DIGEST_SIZE {
+            return Err("Invalid inner size");
+        }
+
+        let word_size = std::mem::size_of::<W>();
+        let param_bytes = self.to_bytes();
+        let mut h = W::IV;
+        for (i, word) in h.iter_mut().enumerate() {
+            *word ^= W::from_le_slice(&param_bytes[i * word_size..(i + 1) * word_size]);
+        }
+        Ok(h)
+    }
 
-        let mut blake2b = BLAKE2b {
-            h: [
-                0x6a09e667f3bcc908,
-                0xbb67ae8584caa73b,
-                0x3c6ef372fe94f82b,
-                0xa54ff53a5f1d36f1,
-                0x510e527fade682d1,
-                0x9b05688c2b3e6c1f,
-                0x1f83d9abfb41bd6b,
-                0x5be0cd19137e2179,
-            ],
-            t: [0, 0],
-            f: [0, 0],
+    pub fn build(&self) -> Result<BLAKE2State<W>, &'static str> {
+        let h = self.initial_state()?;
+        let mut state = BLAKE2State {
+            h,
+            h0: h,
+            key: self.key.clone(),
+            t: [W::ZERO; 2],
+            f: [W::ZERO; 2],
             buflen: 0,
             buf: [0; BLOCK_SIZE],
-            key,
+            digest_size: self.digest_size,
+            last_node: self.last_node,
             finalized: false,
         };
+        state.reset();
+
+        Ok(state)
+    }
+}
+
+impl<W: BLAKE2Word> Default for BLAKE2Params<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Hex output and MAC checking, shared by every BLAKE2 variant
+pub trait BLAKE2Digest: Hasher<Output = Vec<u8>> {
+    fn hexdigest(&mut self) -> String {
+        self.finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    // Compares the digest against an expected MAC tag in constant time
+    fn verify(&mut self, tag: &[u8]) -> bool {
+        let digest = self.finalize();
+        if digest.len() != tag.len() {
+            return false;
+        }
 
-        blake2b.t[0] = digest_size as u64;
-        if let Some(key) = &blake2b.key {
-            blake2b.t[1] = KEY_SIZE as u64;
-            blake2b.buflen = BLOCK_SIZE;
-            blake2b.update(key);
+        let mut diff = 0u8;
+        for (a, b) in digest.iter().zip(tag.iter()) {
+            diff |= a ^ b;
         }
+        diff == 0
+    }
+}
+
+// BLAKE2b over u64 words, BLAKE2s over u32 words
+pub struct BLAKE2State<W: BLAKE2Word> {
+    h: [W; 8],
+    // Parameter-block state and key, kept so reset() can start over
+    h0: [W; 8],
+    key: Vec<u8>,
+    t: [W; 2],
+    f: [W; 2],
+    buflen: usize,
+    // Sized for BLAKE2b; BLAKE2s only uses the first W::BLOCK_SIZE bytes
+    buf: [u8; BLOCK_SIZE],
+    digest_size: usize,
+    last_node: bool,
+    finalized: bool,
+}
+
+pub type BLAKE2b = BLAKE2State<u64>;
+pub type BLAKE2s = BLAKE2State<u32>;
 
-        Ok(blake2b)
+impl<W: BLAKE2Word> BLAKE2State<W> {
+    pub fn new(digest_size: usize, key: Option<&[u8]>) -> Result<Self, &'static str> {
+        BLAKE2Params::new()
+            .digest_size(digest_size)
+            .key(key.unwrap_or(&[]))
+            .build()
     }
 
-    fn init(&mut self, salt: [u8; SALT_SIZE], personal: [u8; PERSONAL_SIZE]) {
+    fn finalize_state(&mut self) {
         if self.finalized {
             return;
         }
 
-        let mut param_bytes = [0u8; 64];
+        self.increment_counter(W::from_usize(self.buflen));
+        self.f[0] = W::FLAG;
+        if self.last_node {
+            self.f[1] = W::FLAG;
+        }
 
-        param_bytes[0] = self.h.len() as u8;
-        param_bytes[1] = DIGEST_SIZE as u8;
-        param_bytes[2] = 1; // fanout
-        param_bytes[3] = 1; // depth
-        param_bytes[4..8].copy_from_slice(&(BLOCK_SIZE as u32).to_le_bytes());
-        param_bytes[8..12].copy_from_slice(&(0u32).to_le_bytes()); // node offset
-        param_bytes[12] = 0; // node depth
-        param_bytes[13] = 0; // inner length
-        param_bytes[14..30].copy_from_slice(&salt);
-        param_bytes[30..46].copy_from_slice(&personal);
-
-        let mut params = [0u64; 8];
-        for i in 0..8 {
-            params[i] = u64::from_le_bytes(param_bytes[i * 8..(i + 1) * 8].try_into().unwrap());
+        for i in self.buflen..W::BLOCK_SIZE {
+            self.buf[i] = 0;
         }
 
+        self.compress();
+        self.finalized = true;
+    }
+
+    // Little-endian serialization of the first 'len' bytes of the state
+    fn output(&self, len: usize) -> Vec<u8> {
+        let word_size = std::mem::size_of::<W>();
+        let mut result = vec![0u8; len];
+        for (i, byte) in result.iter_mut().enumerate() {
+            *byte = self.h[i / word_size].le_byte(i % word_size);
+        }
+        result
+    }
+
+    fn increment_counter(&mut self, inc: W) {
+        self.t[0] = self.t[0].wrapping_add(inc);
+        if self.t[0] < inc {
+            self.t[1] = self.t[1].wrapping_add(W::from_usize(1));
+        }
+    }
+
+    fn compress(&mut self) {
+        let word_size = std::mem::size_of::<W>();
+        let mut v = [W::ZERO; 16];
+        let mut m = [W::ZERO; 16];
+
+        // Convert the input block (self.buf) into an array of words (little-endian)
+        for (i, word) in m.iter_mut().enumerate() {
+            *word = W::from_le_slice(&self.buf[i * word_size..(i + 1) * word_size]);
+        }
+
+        // Initialize the working vector 'v' with the current state 'h' and the IV
+        v[..8].copy_from_slice(&self.h);
+        v[8..].copy_from_slice(&W::IV);
+        v[12] ^= self.t[0];
+        v[13] ^= self.t[1];
+        v[14] ^= self.f[0];
+        v[15] ^= self.f[1];
+
+        // Mixing function 'G' applied to columns, then diagonals, in 12 or 10 rounds
+        for round in 0..W::ROUNDS {
+            let s = &SIGMA[round % 10];
+            Self::g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+            Self::g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+            Self::g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+            Self::g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+            Self::g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+            Self::g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+            Self::g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+            Self::g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+        }
+
+        // Update the state 'h' with the result
         for i in 0..8 {
-            self.h[i] ^= params[i];
+            self.h[i] ^= v[i] ^ v[i + 8];
         }
     }
 
+    // Mixing function 'G'
+    fn g(v: &mut [W; 16], a: usize, b: usize, c: usize, d: usize, x: W, y: W) {
+        let [rot1, rot2, rot3, rot4] = W::ROTATIONS;
+        v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+        v[d] = (v[d] ^ v[a]).rotate_right(rot1);
+        v[c] = v[c].wrapping_add(v[d]);
+        v[b] = (v[b] ^ v[c]).rotate_right(rot2);
+        v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+        v[d] = (v[d] ^ v[a]).rotate_right(rot3);
+        v[c] = v[c].wrapping_add(v[d]);
+        v[b] = (v[b] ^ v[c]).rotate_right(rot4);
+    }
+}
+
+impl<W: BLAKE2Word> Hasher for BLAKE2State<W> {
+    type Output = Vec<u8>;
+
+    // Input after finalize() is a caller bug; release builds ignore it
     fn update(&mut self, data: &[u8]) {
+        debug_assert!(!self.finalized, "update after finalize; call reset first");
         if self.finalized {
             return;
         }
 
-        let mut data_ptr = 0;
-        let data_len = data.len();
         let mut offset = 0;
 
-        while offset < data_len {
-            if self.buflen == BLOCK_SIZE {
-                self.t[0] += BLOCK_SIZE as u64;
-                if self.t[0] < BLOCK_SIZE as u64 {
-                    self.t[1] += 1;
-                }
+        while offset < data.len() {
+            // Only compress a full buffer once more data arrives, so the
+            // last block is always left for finalize
+            if self.buflen == W::BLOCK_SIZE {
+                self.increment_counter(W::from_usize(W::BLOCK_SIZE));
                 self.compress();
                 self.buflen = 0;
             }
 
-            self.buf[self.buflen] = data[offset];
-            self.buflen += 1;
-            data_ptr += 1;
-            offset += 1;
+            let take = (W::BLOCK_SIZE - self.buflen).min(data.len() - offset);
+            self.buf[self.buflen..self.buflen + take].copy_from_slice(&data[offset..offset + take]);
+            self.buflen += take;
+            offset += take;
         }
     }
 
-    fn finalize(&mut self) -> [u8; DIGEST_SIZE] {
-        if self.finalized {
-            return Default::default();
-        }
+    fn finalize(&mut self) -> Vec<u8> {
+        self.finalize_state();
+        self.output(self.digest_size)
+    }
+
+    fn reset(&mut self) {
+        self.h = self.h0;
+        self.t = [W::ZERO; 2];
+        self.f = [W::ZERO; 2];
+        self.buflen = 0;
+        self.buf = [0; BLOCK_SIZE];
+        self.finalized = false;
 
-        self.t[0] += self.buflen as u64;
-        if self.t[0] < self.buflen as u64 {
-            self.t[1] += 1;
+        // The key is zero-padded to a full block and processed as the first block
+        if !self.key.is_empty() {
+            self.buf[..self.key.len()].copy_from_slice(&self.key);
+            self.buflen = W::BLOCK_SIZE;
         }
+    }
+}
 
-        self.f[0] = MASK64BITS;
-        self.f[1] = MASK64BITS;
+impl_io_write!(<W: BLAKE2Word> BLAKE2State<W>);
+
+impl<W: BLAKE2Word> BLAKE2Digest for BLAKE2State<W> {}
+
+// BLAKE2bp: four BLAKE2b leaves hashing interleaved 128-byte blocks, with a
+// root node over the four leaf digests
+const PARALLELISM_DEGREE: usize = 4;
+
+pub struct BLAKE2bp {
+    leaves: Vec<BLAKE2b>,
+    root: BLAKE2b,
+    leaf: usize,
+    blockpos: usize,
+}
 
-        for i in 0..self.buflen {
-            self.buf[i] ^= MASK8BITS;
+impl BLAKE2bp {
+    pub fn new(digest_size: usize, key: Option<&[u8]>) -> Result<Self, &'static str> {
+        let mut leaves = Vec::with_capacity(PARALLELISM_DEGREE);
+        for i in 0..PARALLELISM_DEGREE {
+            leaves.push(Self::leaf_params(digest_size, key, i).build()?);
         }
 
-        self.compress();
+        // The root records the key length but does not absorb the key block
+        let mut root = Self::root_params(digest_size, key).build()?;
+        root.key.clear();
+        root.reset();
+
+        Ok(BLAKE2bp {
+            leaves,
+            root,
+            leaf: 0,
+            blockpos: 0,
+        })
+    }
+
+    fn leaf_params(digest_size: usize, key: Option<&[u8]>, offset: usize) -> BLAKE2bParams {
+        BLAKE2bParams::new()
+            .digest_size(digest_size)
+            .key(key.unwrap_or(&[]))
+            .fanout(PARALLELISM_DEGREE as u8)
+            .depth(2)
+            .node_offset(offset as u64)
+            .inner_size(DIGEST_SIZE)
+            .last_node(offset == PARALLELISM_DEGREE - 1)
+    }
 
-        let mut result = [0u8; DIGEST_SIZE];
-        for i in 0..DIGEST_SIZE {
-            result[i] = (self.h[i / WORD_SIZE] >> (8 * (i % WORD_SIZE))) as u8;
+    fn root_params(digest_size: usize, key: Option<&[u8]>) -> BLAKE2bParams {
+        BLAKE2bParams::new()
+            .digest_size(digest_size)
+            .key(key.unwrap_or(&[]))
+            .fanout(PARALLELISM_DEGREE as u8)
+            .depth(2)
+            .node_depth(1)
+            .inner_size(DIGEST_SIZE)
+            .last_node(true)
+    }
+
+    // One-shot BLAKE2bp hashing each leaf on its own thread
+    pub fn hash_parallel(
+        digest_size: usize,
+        key: Option<&[u8]>,
+        data: &[u8],
+    ) -> Result<Vec<u8>, &'static str> {
+        let mut leaves = Vec::with_capacity(PARALLELISM_DEGREE);
+        for i in 0..PARALLELISM_DEGREE {
+            leaves.push(Self::leaf_params(digest_size, key, i).build()?);
         }
 
-        self.finalized = true;
-        result
+        let leaf_digests: Vec<Vec<u8>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = leaves
+                .into_iter()
+                .enumerate()
+                .map(|(i, mut leaf)| {
+                    scope.spawn(move || {
+                        for block in data.chunks(BLOCK_SIZE).skip(i).step_by(PARALLELISM_DEGREE) {
+                            leaf.update(block);
+                        }
+                        leaf.finalize_state();
+                        leaf.output(DIGEST_SIZE)
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        let mut root = Self::root_params(digest_size, key).build()?;
+        root.key.clear();
+        root.reset();
+        for digest in &leaf_digests {
+            root.update(digest);
+        }
+        Ok(root.finalize())
     }
+}
 
-    fn hexdigest(&mut self) -> String {
-        let digest = self.finalize();
-        let hex_chars: Vec<String> = digest.iter().map(|&byte| format!("{:02x}", byte)).collect();
-        hex_chars.join("")
+impl Hasher for BLAKE2bp {
+    type Output = Vec<u8>;
+
+    fn update(&mut self, data: &[u8]) {
+        let mut offset = 0;
+
+        while offset < data.len() {
+            let take = (BLOCK_SIZE - self.blockpos).min(data.len() - offset);
+            self.leaves[self.leaf].update(&data[offset..offset + take]);
+            self.blockpos += take;
+            offset += take;
+
+            if self.blockpos == BLOCK_SIZE {
+                self.blockpos = 0;
+                self.leaf = (self.leaf + 1) % PARALLELISM_DEGREE;
+            }
+        }
     }
 
-    fn compress(&mut self) {
-        let mut v: [u64; 16] = [0; 16];
-        let mut m: [u64; 16] = [0; 16];
-
-        // Convert the input block (self.buf) into an array of u64 words (little-endian)
-        for i in 0..16 {
-            m[i] = u64::from_le_bytes([
-                self.buf[i * 8],
-                self.buf[i * 8 + 1],
-                self.buf[i * 8 + 2],
-                self.buf[i * 8 + 3],
-                self.buf[i * 8 + 4],
-                self.buf[i * 8 + 5],
-                self.buf[i * 8 + 6],
-                self.buf[i * 8 + 7],
-            ]);
-        }
-
-        // Initialize the working vector 'v' with the current state 'h'
-        v.copy_from_slice(&self.h);
-
-        // Mixing function 'G' applied in 12 rounds
-        for round in 0..12 {
-            let sigma = SIGMA[round];
-            for i in 0..16 {
-                // Mixing function 'G'
-                v = self.g(v, i, sigma[i % 10], m);
+    fn finalize(&mut self) -> Vec<u8> {
+        if !self.root.finalized {
+            for leaf in self.leaves.iter_mut() {
+                leaf.finalize_state();
+                self.root.update(&leaf.output(DIGEST_SIZE));
             }
         }
+        self.root.finalize()
+    }
 
-        // Update the state 'h' with the result
-        for i in 0..8 {
-            self.h[i] ^= v[i] ^ v[i + 8];
+    fn reset(&mut self) {
+        for leaf in self.leaves.iter_mut() {
+            leaf.reset();
         }
+        self.root.reset();
+        self.leaf = 0;
+        self.blockpos = 0;
     }
+}
 
-    // Mixing function 'G'
-    fn g(&self, mut v: [u64; 16], i: usize, sigma: [usize; 16], m: [u64; 16]) -> [u64; 16] {
-        let (mut a, mut b, mut c, mut d) = (v[sigma[0]], v[sigma[1]], v[sigma[2]], v[sigma[3]]);
+impl_io_write!(BLAKE2bp);
 
-        a = a
-            .wrapping_add(b)
-            .wrapping_add(m[sigma[0]])
-            .wrapping_add(self.c[2 * i]);
-        d ^= a;
-        d = d.rotate_right(ROT1);
+impl BLAKE2Digest for BLAKE2bp {}
 
-        c = c.wrapping_add(d);
-        b ^= c;
-        b = b.rotate_right(ROT2);
+// Largest output Hasher::finalize returns in one buffer (1 MiB)
+const XOF_FINALIZE_LIMIT: u64 = 1 << 20;
 
-        a = a
-            .wrapping_add(b)
-            .wrapping_add(m[sigma[1]])
-            .wrapping_add(self.c[2 * i + 1]);
-        d ^= a;
-        d = d.rotate_right(ROT3);
+// BLAKE2Xb extendable-output function: the input is hashed once into a
+// 64-byte root digest H0, and output block i is BLAKE2b(H0) with node offset i
+pub struct BLAKE2Xb {
+    root: BLAKE2b,
+    params: BLAKE2bParams,
+    h0: Vec<u8>,
+    block: Vec<u8>,
+    position: u64,
+}
 
-        c = c.wrapping_add(d);
-        b ^= c;
-        b = b.rotate_right(ROT4);
+impl BLAKE2Xb {
+    pub fn new(xof_length: u32, key: Option<&[u8]>) -> Result<Self, &'static str> {
+        Self::with_params(BLAKE2bParams::new().key(key.unwrap_or(&[])), xof_length)
+    }
 
-        v[sigma[0]] = a;
-        v[sigma[1]] = b;
-        v[sigma[2]] = c;
-        v[sigma[3]] = d;
+    // Uses the key, salt and personalization from 'params'
+    pub fn with_params(params: BLAKE2bParams, xof_length: u32) -> Result<Self, &'static str> {
+        if xof_length == 0 {
+            return Err("Invalid output length");
+        }
 
-        v
+        let params = params.digest_size(DIGEST_SIZE).xof_length(xof_length);
+        let root = params.build()?;
+
+        Ok(BLAKE2Xb {
+            root,
+            params,
+            h0: Vec::new(),
+            block: Vec::new(),
+            position: 0,
+        })
+    }
+
+    // Fills 'out' with the next output bytes and returns how many were written;
+    // returns 0 once all xof_length bytes have been read
+    pub fn read(&mut self, out: &mut [u8]) -> usize {
+        if self.h0.is_empty() {
+            self.h0 = self.root.finalize();
+        }
+
+        let xof_length = self.params.xof_length as u64;
+        let mut written = 0;
+
+        while written < out.len() && self.position < xof_length {
+            let block_pos = (self.position % DIGEST_SIZE as u64) as usize;
+            if block_pos == 0 {
+                self.block = self.output_block(self.position / DIGEST_SIZE as u64);
+            }
+
+            let take = (self.block.len() - block_pos).min(out.len() - written);
+            out[written..written + take].copy_from_slice(&self.block[block_pos..block_pos + take]);
+            written += take;
+            self.position += take as u64;
+        }
+
+        written
+    }
+
+    fn output_block(&self, index: u64) -> Vec<u8> {
+        let remaining = self.params.xof_length as u64 - index * DIGEST_SIZE as u64;
+        let mut block = self
+            .params
+            .clone()
+            .digest_size(remaining.min(DIGEST_SIZE as u64) as usize)
+            .key(&[])
+            .fanout(0)
+            .depth(0)
+            .leaf_size(DIGEST_SIZE as u32)
+            .node_offset(index)
+            .node_depth(0)
+            .inner_size(DIGEST_SIZE)
+            .build()
+            .unwrap();
+        block.update(&self.h0);
+        block.finalize()
     }
 }
 
-fn main() {
-    let data = b"Hello, BLAKE2b!";
-    let key: Option<[u8; KEY_SIZE]> = None;
-    let salt: [u8; SALT_SIZE] = [0u8; SALT_SIZE];
-    let personal: [u8; PERSONAL_SIZE] = [0u8; PERSONAL_SIZE];
+impl Hasher for BLAKE2Xb {
+    type Output = Vec<u8>;
 
-    let mut blake2b = BLAKE2b::new(DIGEST_SIZE, key).unwrap();
-    blake2b.init(salt, personal);
-    blake2b.update(data);
+    fn update(&mut self, data: &[u8]) {
+        self.root.update(data);
+    }
 
-    let hex_digest = blake2b.hexdigest();
+    // Reads the remaining output in one go. Outputs can be up to 4 GiB, so
+    // anything over XOF_FINALIZE_LIMIT must be streamed with read() instead
+    fn finalize(&mut self) -> Vec<u8> {
+        let remaining = self.params.xof_length as u64 - self.position;
+        assert!(
+            remaining <= XOF_FINALIZE_LIMIT,
+            "BLAKE2Xb output too long for finalize; stream it with read"
+        );
+        let mut out = vec![0u8; remaining as usize];
+        self.read(&mut out);
+        out
+    }
 
-    println!("Hex Digest: {}", hex_digest);
+    fn reset(&mut self) {
+        self.root.reset();
+        self.h0.clear();
+        self.block.clear();
+        self.position = 0;
+    }
+}
+
+impl_io_write!(BLAKE2Xb);
+
+impl BLAKE2Digest for BLAKE2Xb {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hasher::digest_chunked;
+    use std::io::{self, Cursor};
+
+    fn hex_to_bytes(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn blake2b_hex(digest_size: usize, key: Option<&[u8]>, data: &[u8]) -> String {
+        let mut blake2b = BLAKE2b::new(digest_size, key).unwrap();
+        blake2b.update(data);
+        blake2b.hexdigest()
+    }
+
+    #[test]
+    fn blake2b_known_answers() {
+        // RFC 7693 Appendix A and common unkeyed vectors
+        let test_cases: [(&[u8], usize, &str); 4] = [
+            (
+                b"abc",
+                64,
+                "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+                 7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+            ),
+            (
+                b"",
+                64,
+                "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419\
+                 d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce",
+            ),
+            (
+                b"abc",
+                32,
+                "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319",
+            ),
+            (
+                b"The quick brown fox jumps over the lazy dog",
+                64,
+                "a8add4bdddfd93e4877d2746e62817b116364a1fa7bc148d95090bc7333b3673\
+                 f82401cf7aa2e4cb1ecd90296e3f14cb5413f8ed77be73045b13914cdcd6a918",
+            ),
+        ];
+
+        for (input, digest_size, expected) in test_cases {
+            assert_eq!(
+                blake2b_hex(digest_size, None, input),
+                expected,
+                "input {:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn blake2b_kat() {
+        // Official blake2b-kat.txt: key = 00..3f, input = 00 01 02 .. (len - 1)
+        let mut key = [0u8; KEY_SIZE];
+        for (i, byte) in key.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+
+        let test_cases = [
+            (0, "10ebb67700b1868efb4417987acf4690ae9d972fb7a590c2f02871799aaa4786b5e996e8f0f4eb981fc214b005f42d2ff4233499391653df7aefcbc13fc51568"),
+            (1, "961f6dd1e4dd30f63901690c512e78e4b45e4742ed197c3c5e45c549fd25f2e4187b0bc9fe30492b16b0d0bc4ef9b0f34c7003fac09a5ef1532e69430234cebd"),
+            (2, "da2cfbe2d8409a0f38026113884f84b50156371ae304c4430173d08a99d9fb1b983164a3770706d537f49e0c916d9f32b95cc37a95b99d857436f0232c88a965"),
+            (127, "76d2d819c92bce55fa8e092ab1bf9b9eab237a25267986cacf2b8ee14d214d730dc9a5aa2d7b596e86a1fd8fa0804c77402d2fcd45083688b218b1cdfa0dcbcb"),
+            (128, "72065ee4dd91c2d8509fa1fc28a37c7fc9fa7d5b3f8ad3d0d7a25626b57b1b44788d4caf806290425f9890a3a2a35a905ab4b37acfd0da6e4517b2525c9651e4"),
+            (129, "64475dfe7600d7171bea0b394e27c9b00d8e74dd1e416a79473682ad3dfdbb706631558055cfc8a40e07bd015a4540dcdea15883cbbf31412df1de1cd4152b91"),
+            (255, "142709d62e28fcccd0af97fad0f8465b971e82201dc51070faa0372aa43e92484be1c1e73ba10906d5d1853db6a4106e0a7bf9800d373d6dee2d46d62ef2a461"),
+        ];
+
+        for (len, expected) in test_cases {
+            let input: Vec<u8> = (0..len).map(|x| x as u8).collect();
+            assert_eq!(
+                blake2b_hex(DIGEST_SIZE, Some(&key), &input),
+                expected,
+                "len {}",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn blake2b_digest_sizes() {
+        // Hash of the hashes for every digest size 1..=64 over several input
+        // lengths, with expected value taken from the reference implementation
+        let expected = "f66322694b9ff0739372dd37f23a52d506adf760a681baf95d59286756a25c9b";
+        let mut grand = BLAKE2b::new(32, None).unwrap();
+
+        for digest_size in 1..=DIGEST_SIZE {
+            for &len in &[0usize, 3, 128, 129, 255, 1024] {
+                let input: Vec<u8> = (0..len).map(|x| (x % 251) as u8).collect();
+                let mut blake2b = BLAKE2b::new(digest_size, None).unwrap();
+                // Feed in uneven chunks to exercise the buffering
+                for chunk in input.chunks(7) {
+                    blake2b.update(chunk);
+                }
+                grand.update(&blake2b.finalize());
+            }
+        }
+
+        assert_eq!(grand.hexdigest(), expected);
+    }
+
+    // Deterministic input generator from RFC 7693 Appendix E
+    fn selftest_seq(len: usize, seed: u32) -> Vec<u8> {
+        let mut a = 0xDEAD4BADu32.wrapping_mul(seed);
+        let mut b = 1u32;
+        let mut out = Vec::with_capacity(len);
+
+        for _ in 0..len {
+            let t = a.wrapping_add(b);
+            a = b;
+            b = t;
+            out.push((t >> 24) as u8);
+        }
+        out
+    }
+
+    #[test]
+    fn blake2b_rfc7693_selftest() {
+        // RFC 7693 Appendix E: hash of unkeyed and keyed hashes
+        let expected = "c23a7800d98123bd10f506c61e29da5603d763b8bbad2e737f5e765a7bccd475";
+        let mut grand = BLAKE2b::new(32, None).unwrap();
+
+        for &digest_size in &[20usize, 32, 48, 64] {
+            for &len in &[0usize, 3, 128, 129, 255, 1024] {
+                let input = selftest_seq(len, len as u32);
+                let key = selftest_seq(digest_size, digest_size as u32);
+
+                let mut unkeyed = BLAKE2b::new(digest_size, None).unwrap();
+                unkeyed.update(&input);
+                grand.update(&unkeyed.finalize());
+
+                let mut keyed = BLAKE2b::new(digest_size, Some(&key)).unwrap();
+                keyed.update(&input);
+                grand.update(&keyed.finalize());
+            }
+        }
+
+        assert_eq!(grand.hexdigest(), expected);
+    }
+
+    #[test]
+    fn blake2b_mac() {
+        // (input, key, salt, personal, digest size, expected)
+        type MacCase<'a> = (&'a [u8], &'a [u8], &'a [u8], &'a [u8], usize, &'a str);
+        let test_cases: [MacCase; 3] = [
+            (
+                b"message",
+                b"secret key",
+                b"",
+                b"",
+                32,
+                "f71324f0d1339cc29166e351477087fdabee524aea02eb2ff2b79f52eeaea4e4",
+            ),
+            (
+                b"abc",
+                b"k",
+                b"saltsalt",
+                b"personal",
+                64,
+                "edffe0a3c0a6e5ce8228689bf989468799b46f38c20babae6e87f16794cbd6ef\
+                 b29359405d22d5d1af7a25626877c9af160e46be04c03607f64049d84a83ac02",
+            ),
+            (
+                b"",
+                b"",
+                &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+                &[
+                    16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31,
+                ],
+                20,
+                "8fbe8f993d7c320c18547eaec8be17f90d1c48b1",
+            ),
+        ];
+
+        for (input, key, salt, personal, digest_size, expected) in test_cases {
+            let params = BLAKE2bParams::new()
+                .digest_size(digest_size)
+                .key(key)
+                .salt(salt)
+                .personal(personal);
+
+            let mut blake2b = params.build().unwrap();
+            blake2b.update(input);
+            assert_eq!(blake2b.hexdigest(), expected);
+
+            let mut blake2b = params.build().unwrap();
+            blake2b.update(input);
+            let mut tag = blake2b.finalize();
+            let mut verifier = params.build().unwrap();
+            verifier.update(input);
+            assert!(verifier.verify(&tag));
+
+            tag[0] ^= 1;
+            let mut verifier = params.build().unwrap();
+            verifier.update(input);
+            assert!(!verifier.verify(&tag));
+        }
+    }
+
+    #[test]
+    fn blake2b_invalid_parameters_rejected() {
+        assert!(BLAKE2bParams::new().digest_size(0).build().is_err());
+        assert!(BLAKE2bParams::new()
+            .digest_size(DIGEST_SIZE + 1)
+            .build()
+            .is_err());
+        assert!(BLAKE2bParams::new()
+            .key(&[0; KEY_SIZE + 1])
+            .build()
+            .is_err());
+        assert!(BLAKE2bParams::new()
+            .salt(&[0; SALT_SIZE + 1])
+            .build()
+            .is_err());
+        assert!(BLAKE2bParams::new()
+            .personal(&[0; PERSONAL_SIZE + 1])
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn blake2b_tree() {
+        // Two-leaf tree: fanout 2, depth 2, 4096-byte leaves, 32-byte inner hashes
+        let expected = "fca8351367f8511bc481e545273f94af0466a10c20a46361f3f1707910c3fbce";
+        let data: Vec<u8> = (0..6000).map(|x| (x % 251) as u8).collect();
+        let tree = |offset: u64, depth: u8, last: bool| {
+            BLAKE2bParams::new()
+                .digest_size(32)
+                .fanout(2)
+                .depth(2)
+                .leaf_size(4096)
+                .inner_size(32)
+                .node_offset(offset)
+                .node_depth(depth)
+                .last_node(last)
+                .build()
+                .unwrap()
+        };
+
+        let mut left = tree(0, 0, false);
+        left.update(&data[..4096]);
+        let mut right = tree(1, 0, true);
+        right.update(&data[4096..]);
+        let mut root = tree(0, 1, true);
+        root.update(&left.finalize());
+        root.update(&right.finalize());
+
+        assert_eq!(root.hexdigest(), expected);
+    }
+
+    #[test]
+    fn blake2bp_known_answers() {
+        // Unkeyed reference BLAKE2bp vectors, input = 00 01 02 .. (len - 1)
+        let test_cases = [
+            (0, "b5ef811a8038f70b628fa8b294daae7492b1ebe343a80eaabbf1f6ae664dd67b9d90b0120791eab81dc96985f28849f6a305186a85501b405114bfa678df9380"),
+            (129, "b545880294afa153f8b9f49c73d952b5d1228f1a1ab5ebcb05ff79e560c030f7500fe256a40b6a0e6cb3d42acd4b98595c5b51eaec5ad69cd40f1fc16d2d5f50"),
+            (512, "5b3a0e990c4e8c6e5463e763a6686551a129a81ab48c49cd8dc10519dfe2d02d2a451cbba6511775b6a9cb26db88363cdd067ffb7183efe19826678b2fc9f349"),
+            (513, "cd79fbbded91823272abb7a97a5530608f0583bd5405c7765156c4d8754ddf435d6d71b84f83c6381078935e378d4bf0f752b309d1398af578e103e443b8ac55"),
+            (2000, "00f7225ffd39738b39c64b1f9bc4ea3a8b68d34987026364532dae32ea07421e03566eb936ff4f7e63b7c783c20edb642c82a8a3554989dd6357256e61a0b193"),
+        ];
+
+        for (len, expected) in test_cases {
+            let input: Vec<u8> = (0..len).map(|x| x as u8).collect();
+
+            let mut blake2bp = BLAKE2bp::new(DIGEST_SIZE, None).unwrap();
+            for chunk in input.chunks(100) {
+                blake2bp.update(chunk);
+            }
+            assert_eq!(blake2bp.hexdigest(), expected, "len {}", len);
+
+            let parallel = BLAKE2bp::hash_parallel(DIGEST_SIZE, None, &input).unwrap();
+            assert_eq!(parallel, hex_to_bytes(expected), "len {}", len);
+        }
+
+        // Official blake2bp-kat.txt, first keyed vector (key = 00..3f, empty input)
+        let expected = "9d9461073e4eb640a255357b839f394b838c6ff57c9b686a3f76107c1066728f3c9956bd785cbc3bf79dc2ab578c5a0c063b9d9c405848de1dbe821cd05c940a";
+        let key: Vec<u8> = (0..KEY_SIZE as u8).collect();
+        let mut blake2bp = BLAKE2bp::new(DIGEST_SIZE, Some(&key)).unwrap();
+        assert_eq!(blake2bp.hexdigest(), expected);
+    }
+
+    #[test]
+    fn blake2xb_kat() {
+        // Official blake2xb-kat.txt: key = 00..3f, input = 00..ff
+        let key: Vec<u8> = (0..KEY_SIZE as u8).collect();
+        let input: Vec<u8> = (0..=255u8).collect();
+        let test_cases = [
+            (1, "64"),
+            (2, "f457"),
+            (64, "4324561d76c370ef35ac36a4adf8f3773a50d86504bd284f71f7ce9e2bc4c1f1d34a7fb2d67561d101955d448b67577eb30dfee96a95c7f921ef53e20be8bc44"),
+            (65, "78f0ed6e220b3da3cc9381563b2f72c8dc830cb0f39a48c6ae479a6a78dcfa94002631dec467e9e9b47cc8f0887eb680e340aec3ec009d4a33d241533c76c8ca8c"),
+            (200, "8ca704fe7208fe5f9c23110c0b3b4eee0ef632cae82bda68d8db2436ad409aa05cf159223586e1e6d8bdae9f316ea786809fbe7fe81ec61c61552d3a83cd6beaf652d1263862664df6aae321d0323440430f400f291c3efbe5d5c690b0cc6b0bf871b3933befb40bc870e2ee1ebb68025a2dcc11b68daadef6be29b5f21e440374301bde1e80dcfade4c9d681480e65ec494a6af48df232c3d51447b9d06be714949249c44c43cf73ed13ef0d533e770284e51369d94ae241a5fb2f163893071b2b4c118aeaf9eae"),
+        ];
+
+        for (len, expected) in test_cases {
+            let mut blake2xb = BLAKE2Xb::new(len, Some(&key)).unwrap();
+            blake2xb.update(&input);
+            assert_eq!(blake2xb.hexdigest(), expected, "len {}", len);
+
+            // Reading in small pieces must give the same stream
+            let mut streamed = BLAKE2Xb::new(len, Some(&key)).unwrap();
+            streamed.update(&input);
+            let mut stream = Vec::new();
+            let mut piece = [0u8; 7];
+            loop {
+                let n = streamed.read(&mut piece);
+                if n == 0 {
+                    break;
+                }
+                stream.extend_from_slice(&piece[..n]);
+            }
+
+            assert_eq!(stream, hex_to_bytes(expected), "len {}", len);
+        }
+    }
+
+    #[test]
+    fn blake2xb_salt_and_personal() {
+        let expected =
+            "27fef14350af98466bd3498f4c2988a507c33a2fed96548cbc1743e601be07b9d3bce3becabb0727";
+        let params = BLAKE2bParams::new()
+            .key(b"k")
+            .salt(b"saltsalt")
+            .personal(b"personal");
+        let mut blake2xb = BLAKE2Xb::with_params(params, 40).unwrap();
+        blake2xb.update(b"abc");
+        assert_eq!(blake2xb.hexdigest(), expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "stream it with read")]
+    fn blake2xb_finalize_rejects_huge_outputs() {
+        let mut blake2xb = BLAKE2Xb::new(u32::MAX, None).unwrap();
+        blake2xb.update(b"abc");
+        blake2xb.finalize();
+    }
+
+    #[test]
+    fn blake2s_known_answers() {
+        let test_cases: [(&[u8], &str); 2] = [
+            (
+                b"abc",
+                "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982",
+            ),
+            (
+                b"",
+                "69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9",
+            ),
+        ];
+
+        for (input, expected) in test_cases {
+            let mut blake2s = BLAKE2s::new(BLAKE2S_DIGEST_SIZE, None).unwrap();
+            blake2s.update(input);
+            assert_eq!(blake2s.hexdigest(), expected, "input {:?}", input);
+        }
+    }
+
+    #[test]
+    fn blake2s_kat() {
+        // Official blake2s-kat.txt: key = 00..1f, input = 00 01 02 .. (len - 1)
+        let key: Vec<u8> = (0..BLAKE2S_KEY_SIZE as u8).collect();
+        let kat_cases = [
+            (
+                0,
+                "48a8997da407876b3d79c0d92325ad3b89cbb754d86ab71aee047ad345fd2c49",
+            ),
+            (
+                1,
+                "40d15fee7c328830166ac3f918650f807e7e01e177258cdc0a39b11f598066f1",
+            ),
+            (
+                63,
+                "c65382513f07460da39833cb666c5ed82e61b9e998f4b0c4287cee56c3cc9bcd",
+            ),
+            (
+                64,
+                "8975b0577fd35566d750b362b0897a26c399136df07bababbde6203ff2954ed4",
+            ),
+            (
+                65,
+                "21fe0ceb0052be7fb0f004187cacd7de67fa6eb0938d927677f2398c132317a8",
+            ),
+            (
+                255,
+                "3fb735061abc519dfe979e54c1ee5bfad0a9d858b3315bad34bde999efd724dd",
+            ),
+        ];
+
+        for (len, expected) in kat_cases {
+            let input: Vec<u8> = (0..len).map(|x| x as u8).collect();
+            let mut blake2s = BLAKE2s::new(BLAKE2S_DIGEST_SIZE, Some(&key)).unwrap();
+            blake2s.update(&input);
+            assert_eq!(blake2s.hexdigest(), expected, "len {}", len);
+        }
+    }
+
+    #[test]
+    fn blake2s_rfc7693_selftest() {
+        // RFC 7693 Appendix E self-test for BLAKE2s
+        let expected = "6a411f08ce25adcdfb02aba641451cec53c598b24f4fc787fbdc88797f4c1dfe";
+        let mut grand = BLAKE2s::new(32, None).unwrap();
+
+        for &digest_size in &[16usize, 20, 28, 32] {
+            for &len in &[0usize, 3, 64, 65, 255, 1024] {
+                let input = selftest_seq(len, len as u32);
+                let key = selftest_seq(digest_size, digest_size as u32);
+
+                let mut unkeyed = BLAKE2s::new(digest_size, None).unwrap();
+                unkeyed.update(&input);
+                grand.update(&unkeyed.finalize());
+
+                let mut keyed = BLAKE2s::new(digest_size, Some(&key)).unwrap();
+                keyed.update(&input);
+                grand.update(&keyed.finalize());
+            }
+        }
+
+        assert_eq!(grand.hexdigest(), expected);
+    }
+
+    #[test]
+    fn blake2s_digest_sizes() {
+        // Every digest size 1..=32, fed in uneven chunks
+        let expected = "575d094d32e939edbacd95d0d7a1cebd01e896371d621532e6a1fea397609feb";
+        let mut grand = BLAKE2s::new(32, None).unwrap();
+
+        for digest_size in 1..=BLAKE2S_DIGEST_SIZE {
+            for &len in &[0usize, 3, 64, 65, 255, 1024] {
+                let input: Vec<u8> = (0..len).map(|x| (x % 251) as u8).collect();
+                let mut blake2s = BLAKE2s::new(digest_size, None).unwrap();
+                for chunk in input.chunks(7) {
+                    blake2s.update(chunk);
+                }
+                grand.update(&blake2s.finalize());
+            }
+        }
+
+        assert_eq!(grand.hexdigest(), expected);
+    }
+
+    #[test]
+    fn blake2s_mac() {
+        // Keyed MAC with salt and personalization
+        let expected = "20320bb52c57a90405c83b7afeabe8c8fa9b744e6b0d17d52e3f96bb9ebf02d5";
+        let params = BLAKE2sParams::new()
+            .key(b"k")
+            .salt(b"saltsalt")
+            .personal(b"personal");
+        let mut blake2s = params.build().unwrap();
+        blake2s.update(b"abc");
+        let tag = blake2s.finalize();
+        let mut verifier = params.build().unwrap();
+        verifier.update(b"abc");
+        assert_eq!(tag, hex_to_bytes(expected));
+        assert!(verifier.verify(&tag));
+    }
+
+    #[test]
+    fn blake2s_tree_parameters() {
+        // Every field of the shorter BLAKE2s parameter block, including a
+        // 48-bit node offset; checked against Python's hashlib.blake2s
+        let expected = "4000d5a01f44ca7350f6e22bb45deb0342dcdde92c605536b0167b37c286da9c";
+        let params = BLAKE2sParams::new()
+            .key(b"key")
+            .salt(b"salt")
+            .personal(b"me")
+            .fanout(2)
+            .depth(3)
+            .leaf_size(4096)
+            .node_offset((1 << 40) + 5)
+            .node_depth(1)
+            .inner_size(32)
+            .last_node(true);
+        let mut blake2s = params.build().unwrap();
+        blake2s.update(b"abc");
+        assert_eq!(blake2s.hexdigest(), expected);
+
+        assert!(params.clone().node_offset(1 << 48).build().is_err());
+        assert!(params
+            .clone()
+            .inner_size(BLAKE2S_DIGEST_SIZE + 1)
+            .build()
+            .is_err());
+        assert!(BLAKE2sParams::new().xof_length(1 << 16).build().is_err());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "update after finalize")]
+    fn update_after_finalize_is_caught() {
+        let mut blake2b = BLAKE2b::new(DIGEST_SIZE, None).unwrap();
+        blake2b.finalize();
+        blake2b.update(b"too late");
+    }
+
+    // After a reset the hasher must agree with a freshly built one, key included
+    fn assert_resets_cleanly<H: Hasher<Output = Vec<u8>>>(
+        name: &str,
+        mut used: H,
+        mut fresh: H,
+        data: &[u8],
+    ) {
+        fresh.update(data);
+        let expected = fresh.finalize();
+
+        used.update(b"stale input");
+        used.finalize();
+        assert_eq!(used.digest(data), expected, "{}", name);
+        assert_eq!(digest_chunked(&mut used, data, 7), expected, "{}", name);
+    }
+
+    #[test]
+    fn hasher_reset_keeps_key() {
+        let data: Vec<u8> = (0..1000u32).map(|i| (i * 31 % 251) as u8).collect();
+        let key = b"reset keeps the key";
+
+        let blake2b = |digest_size| BLAKE2b::new(digest_size, Some(key)).unwrap();
+        let blake2s = || BLAKE2s::new(BLAKE2S_DIGEST_SIZE, Some(key)).unwrap();
+        let blake2bp = || BLAKE2bp::new(DIGEST_SIZE, Some(key)).unwrap();
+        let blake2xb = || BLAKE2Xb::new(200, Some(key)).unwrap();
+
+        assert_resets_cleanly("BLAKE2b", blake2b(DIGEST_SIZE), blake2b(DIGEST_SIZE), &data);
+        assert_resets_cleanly("BLAKE2b-160", blake2b(20), blake2b(20), &data);
+        assert_resets_cleanly("BLAKE2s", blake2s(), blake2s(), &data);
+        assert_resets_cleanly("BLAKE2bp", blake2bp(), blake2bp(), &data);
+        assert_resets_cleanly("BLAKE2Xb", blake2xb(), blake2xb(), &data);
+    }
+
+    #[test]
+    fn reader_and_io_copy() {
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i * 7 + i / 256) as u8).collect();
+        let blake2bp = || BLAKE2bp::new(DIGEST_SIZE, None).unwrap();
+
+        let mut copied = blake2bp();
+        let n = io::copy(&mut Cursor::new(&data), &mut copied).unwrap();
+        assert_eq!(n, data.len() as u64);
+        let digest = copied.finalize();
+        assert_eq!(digest, blake2bp().digest_reader(&data[..]).unwrap());
+        assert_eq!(
+            digest,
+            BLAKE2bp::hash_parallel(DIGEST_SIZE, None, &data).unwrap()
+        );
+    }
 }
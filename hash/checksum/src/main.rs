@@ -0,0 +1,415 @@
+// Hashes files or standard input with the algorithms in hash/, printing
+// sha256sum-style "<digest>  <file>" lines, or verifies such lines.
+//
+//   checksum <algorithm> [FILE]...
+//   checksum <algorithm> --check [MANIFEST]...
+//
+// With no FILE, or when FILE is -, standard input is read.
+
+use hash::{adler32, blake2b, crc32, djb2, fnv1a, murmur3, pearson, xxhash, Hasher};
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::process;
+
+// Lower-case, zero-padded hex of each algorithm's output type
+trait Hex {
+    fn hex(&self) -> String;
+}
+
+impl Hex for u8 {
+    fn hex(&self) -> String {
+        format!("{:02x}", self)
+    }
+}
+
+impl Hex for u32 {
+    fn hex(&self) -> String {
+        format!("{:08x}", self)
+    }
+}
+
+impl Hex for u64 {
+    fn hex(&self) -> String {
+        format!("{:016x}", self)
+    }
+}
+
+impl Hex for u128 {
+    fn hex(&self) -> String {
+        format!("{:032x}", self)
+    }
+}
+
+impl Hex for Vec<u8> {
+    fn hex(&self) -> String {
+        self.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
+
+fn hex_digest<H: Hasher>(mut hasher: H, reader: impl Read) -> io::Result<String>
+where
+    H::Output: Hex,
+{
+    Ok(hasher.digest_reader(reader)?.hex())
+}
+
+// The 128-bit MurmurHash3 digests print in the reference's byte order
+fn le_hex_digest<H: Hasher<Output = u128>>(mut hasher: H, reader: impl Read) -> io::Result<String> {
+    Ok(hasher.digest_reader(reader)?.to_le_bytes().to_vec().hex())
+}
+
+// Catalogue CRCs print as many hex digits as their width needs
+fn crc_hex_digest(params: crc32::CRCParams, reader: impl Read) -> io::Result<String> {
+    let crc = crc32::CRCState::new(params)
+        .unwrap()
+        .digest_reader(reader)?;
+    let digits = params.width.div_ceil(4) as usize;
+    Ok(format!("{:0digits$x}", crc, digits = digits))
+}
+
+type Digest = fn(&mut dyn Read) -> io::Result<String>;
+
+// BLAKE2Xb is left out: its output length has no natural default
+const ALGORITHMS: [(&str, Digest); 35] = [
+    ("crc32", |r| hex_digest(crc32::CRC32State::new(), r)),
+    ("crc8-smbus", |r| crc_hex_digest(crc32::CRC_8_SMBUS, r)),
+    ("crc8-maxim", |r| crc_hex_digest(crc32::CRC_8_MAXIM, r)),
+    ("crc16-ccitt-false", |r| {
+        crc_hex_digest(crc32::CRC_16_CCITT_FALSE, r)
+    }),
+    ("crc16-kermit", |r| crc_hex_digest(crc32::CRC_16_KERMIT, r)),
+    ("crc16-xmodem", |r| crc_hex_digest(crc32::CRC_16_XMODEM, r)),
+    ("crc16-modbus", |r| crc_hex_digest(crc32::CRC_16_MODBUS, r)),
+    ("crc16-arc", |r| crc_hex_digest(crc32::CRC_16_ARC, r)),
+    ("crc24-openpgp", |r| {
+        crc_hex_digest(crc32::CRC_24_OPENPGP, r)
+    }),
+    ("crc32-bzip2", |r| crc_hex_digest(crc32::CRC_32_BZIP2, r)),
+    ("crc32-mpeg2", |r| crc_hex_digest(crc32::CRC_32_MPEG2, r)),
+    ("crc32c", |r| crc_hex_digest(crc32::CRC_32C, r)),
+    ("crc64-xz", |r| crc_hex_digest(crc32::CRC_64_XZ, r)),
+    ("crc64-ecma-182", |r| {
+        crc_hex_digest(crc32::CRC_64_ECMA_182, r)
+    }),
+    ("adler32", |r| hex_digest(adler32::Adler32State::new(), r)),
+    ("djb2", |r| hex_digest(djb2::DJB2Cipher::new(), r)),
+    ("djb2a", |r| hex_digest(djb2::DJB2aCipher::new(), r)),
+    ("sdbm", |r| hex_digest(djb2::SDBMCipher::new(), r)),
+    ("loselose", |r| hex_digest(djb2::LoseLoseCipher::new(), r)),
+    ("fnv1", |r| hex_digest(fnv1a::FNV1::<u32>::new(), r)),
+    ("fnv1-64", |r| hex_digest(fnv1a::FNV1::<u64>::new(), r)),
+    ("fnv1a", |r| hex_digest(fnv1a::FNV1a::<u32>::new(), r)),
+    ("fnv1a-64", |r| hex_digest(fnv1a::FNV1a::<u64>::new(), r)),
+    ("fnv1a-128", |r| hex_digest(fnv1a::FNV1a::<u128>::new(), r)),
+    ("pearson", |r| hex_digest(pearson::Pearson::new(), r)),
+    ("blake2b", |r| {
+        hex_digest(blake2b::BLAKE2b::new(64, None).unwrap(), r)
+    }),
+    ("blake2s", |r| {
+        hex_digest(blake2b::BLAKE2s::new(32, None).unwrap(), r)
+    }),
+    ("blake2bp", |r| {
+        hex_digest(blake2b::BLAKE2bp::new(64, None).unwrap(), r)
+    }),
+    ("murmur3", |r| hex_digest(murmur3::Murmur3x86_32::new(), r)),
+    ("murmur3-x86-128", |r| {
+        le_hex_digest(murmur3::Murmur3x86_128::new(), r)
+    }),
+    ("murmur3-x64-128", |r| {
+        le_hex_digest(murmur3::Murmur3x64_128::new(), r)
+    }),
+    ("xxh32", |r| hex_digest(xxhash::XXH32::new(), r)),
+    ("xxh64", |r| hex_digest(xxhash::XXH64::new(), r)),
+    ("xxh3", |r| hex_digest(xxhash::XXH3_64::new(), r)),
+    ("xxh128", |r| hex_digest(xxhash::XXH3_128::new(), r)),
+];
+
+fn find_algorithm(name: &str) -> Option<Digest> {
+    ALGORITHMS
+        .iter()
+        .find(|(algorithm, _)| *algorithm == name)
+        .map(|&(_, digest)| digest)
+}
+
+fn open(path: &str) -> io::Result<Box<dyn Read>> {
+    if path == "-" {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        Ok(Box::new(File::open(path)?))
+    }
+}
+
+fn hash_files(digest: Digest, files: &[&str]) -> bool {
+    let mut ok = true;
+    for &file in files {
+        match open(file).and_then(|mut reader| digest(&mut reader)) {
+            Ok(hex) => println!("{}  {}", hex, file),
+            Err(e) => {
+                eprintln!("checksum: {}: {}", file, e);
+                ok = false;
+            }
+        }
+    }
+    ok
+}
+
+// Splits "<digest>  <file>" (text mode) or "<digest> *<file>" (binary mode),
+// tolerating the '\r' of a manifest written on Windows
+fn parse_line(line: &str) -> Option<(&str, &str)> {
+    let (expected, rest) = line.trim_end_matches('\r').split_once(' ')?;
+    let file = rest.strip_prefix(' ').or_else(|| rest.strip_prefix('*'))?;
+    if expected.is_empty() || !expected.bytes().all(|b| b.is_ascii_hexdigit()) || file.is_empty() {
+        return None;
+    }
+    Some((expected, file))
+}
+
+fn check_manifests(digest: Digest, manifests: &[&str]) -> bool {
+    let mut ok = true;
+    for &manifest in manifests {
+        let reader = match open(manifest) {
+            Ok(reader) => BufReader::new(reader),
+            Err(e) => {
+                eprintln!("checksum: {}: {}", manifest, e);
+                ok = false;
+                continue;
+            }
+        };
+
+        let (mut checked, mut malformed, mut unreadable, mut mismatched) = (0, 0, 0, 0);
+        for line in reader.lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    eprintln!("checksum: {}: {}", manifest, e);
+                    ok = false;
+                    break;
+                }
+            };
+            let (expected, file) = match parse_line(&line) {
+                Some(parsed) => parsed,
+                None => {
+                    malformed += 1;
+                    continue;
+                }
+            };
+
+            checked += 1;
+            // The manifest's reader already holds the stdin lock
+            let result = if manifest == "-" && file == "-" {
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "standard input is already being read as the checksum list",
+                ))
+            } else {
+                open(file).and_then(|mut reader| digest(&mut reader))
+            };
+            match result {
+                Ok(actual) if actual.eq_ignore_ascii_case(expected) => println!("{}: OK", file),
+                Ok(_) => {
+                    println!("{}: FAILED", file);
+                    mismatched += 1;
+                }
+                Err(e) => {
+                    eprintln!("checksum: {}: {}", file, e);
+                    println!("{}: FAILED open or read", file);
+                    unreadable += 1;
+                }
+            }
+        }
+
+        if checked == 0 {
+            eprintln!(
+                "checksum: {}: no properly formatted checksum lines found",
+                manifest
+            );
+            ok = false;
+            continue;
+        }
+        if malformed > 0 {
+            eprintln!(
+                "checksum: WARNING: {} line(s) improperly formatted",
+                malformed
+            );
+        }
+        if unreadable > 0 {
+            eprintln!(
+                "checksum: WARNING: {} listed file(s) could not be read",
+                unreadable
+            );
+        }
+        if mismatched > 0 {
+            eprintln!(
+                "checksum: WARNING: {} computed checksum(s) did NOT match",
+                mismatched
+            );
+        }
+        ok &= unreadable == 0 && mismatched == 0;
+    }
+    ok
+}
+
+fn usage() -> String {
+    format!(
+        "Usage: checksum <algorithm> [--check] [FILE]...\n\
+         Print or check checksums. With no FILE, or when FILE is -, read standard input.\n\n\
+         \x20 -c, --check  read checksums from the FILEs and check them\n\n\
+         Algorithms: {}\n\n\
+         BLAKE2Xb is not offered, since its output length has no natural default.",
+        ALGORITHMS.map(|(name, _)| name).join(", ")
+    )
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let digest = match args.first().map(String::as_str) {
+        Some("-h") | Some("--help") => {
+            println!("{}", usage());
+            return;
+        }
+        Some(name) => match find_algorithm(name) {
+            Some(digest) => digest,
+            None => {
+                eprintln!("checksum: unknown algorithm '{}'\n{}", name, usage());
+                process::exit(1);
+            }
+        },
+        None => {
+            eprintln!("{}", usage());
+            process::exit(1);
+        }
+    };
+
+    let mut check = false;
+    let mut files = vec![];
+    for arg in &args[1..] {
+        match arg.as_str() {
+            "-c" | "--check" => check = true,
+            option if option.starts_with('-') && option != "-" => {
+                eprintln!("checksum: unknown option '{}'\n{}", option, usage());
+                process::exit(1);
+            }
+            file => files.push(file),
+        }
+    }
+    if files.is_empty() {
+        files.push("-");
+    }
+
+    let ok = if check {
+        check_manifests(digest, &files)
+    } else {
+        hash_files(digest, &files)
+    };
+    if !ok {
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_line_text_and_binary_mode() {
+        assert_eq!(
+            parse_line("352441c2  abc.txt"),
+            Some(("352441c2", "abc.txt"))
+        );
+        assert_eq!(
+            parse_line("352441c2 *abc.bin"),
+            Some(("352441c2", "abc.bin"))
+        );
+        // Only the separator is consumed; the rest is the file name
+        assert_eq!(parse_line("352441c2   lead"), Some(("352441c2", " lead")));
+        assert_eq!(parse_line("352441c2  a b"), Some(("352441c2", "a b")));
+    }
+
+    #[test]
+    fn parse_line_strips_trailing_carriage_return() {
+        assert_eq!(
+            parse_line("352441c2  abc.txt\r"),
+            Some(("352441c2", "abc.txt"))
+        );
+        assert_eq!(parse_line("352441c2  \r"), None);
+    }
+
+    #[test]
+    fn parse_line_rejects_malformed_lines() {
+        assert_eq!(parse_line(""), None);
+        assert_eq!(parse_line("352441c2"), None);
+        assert_eq!(parse_line("352441c2  "), None);
+        assert_eq!(parse_line("352441c2 abc.txt"), None);
+        assert_eq!(parse_line("35244xc2  abc.txt"), None);
+        assert_eq!(parse_line("  abc.txt"), None);
+    }
+
+    // Digest of "abc" under each algorithm, in table order. The catalogue
+    // CRCs, Adler-32, FNV, djb2, BLAKE2 and MurmurHash3 values were checked
+    // against independent implementations, xxHash against the crate's
+    // sanity vectors; Pearson uses the crate's shuffled default table
+    const ABC_DIGESTS: [(&str, &str); 35] = [
+        ("crc32", "352441c2"),
+        ("crc8-smbus", "5f"),
+        ("crc8-maxim", "42"),
+        ("crc16-ccitt-false", "514a"),
+        ("crc16-kermit", "58e9"),
+        ("crc16-xmodem", "9dd6"),
+        ("crc16-modbus", "5749"),
+        ("crc16-arc", "9738"),
+        ("crc24-openpgp", "ba1c7b"),
+        ("crc32-bzip2", "648cbb73"),
+        ("crc32-mpeg2", "9b73448c"),
+        ("crc32c", "364b3fb7"),
+        ("crc64-xz", "2cd8094a1a277627"),
+        ("crc64-ecma-182", "66501a349a0e0855"),
+        ("adler32", "024d0127"),
+        ("djb2", "0b885c8b"),
+        ("djb2a", "0b873285"),
+        ("sdbm", "3025f862"),
+        ("loselose", "00000126"),
+        ("fnv1", "439c2f4b"),
+        ("fnv1-64", "d8dcca186bafadcb"),
+        ("fnv1a", "1a47e90b"),
+        ("fnv1a-64", "e71fa2190541574b"),
+        ("fnv1a-128", "a68d622cec8b5822836dbc7977af7f3b"),
+        ("pearson", "b0"),
+        (
+            "blake2b",
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+             7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+        ),
+        (
+            "blake2s",
+            "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982",
+        ),
+        (
+            "blake2bp",
+            "b91a6b66ae87526c400b0a8b53774dc65284ad8f6575f8148ff93dff943a6ecd\
+             8362130f22d6dae633aa0f91df4ac89aaff31d0f1b923c898e82025dedbdad6e",
+        ),
+        ("murmur3", "b3dd93fa"),
+        ("murmur3-x86-128", "d1c6cd75a506b0a2a506b0a2a506b0a2"),
+        ("murmur3-x64-128", "6778ad3f3f3f96b4522dca264174a23b"),
+        ("xxh32", "32d153ff"),
+        ("xxh64", "44bc2cf5ad770999"),
+        ("xxh3", "78af5f94892f3950"),
+        ("xxh128", "06b05ab6733a618578af5f94892f3950"),
+    ];
+
+    #[test]
+    fn algorithm_table() {
+        assert_eq!(
+            ALGORITHMS.map(|(name, _)| name),
+            ABC_DIGESTS.map(|(name, _)| name)
+        );
+        for (name, expected) in ABC_DIGESTS {
+            let digest = find_algorithm(name).unwrap();
+            assert_eq!(digest(&mut "abc".as_bytes()).unwrap(), expected, "{}", name);
+        }
+        assert!(find_algorithm("md5").is_none());
+        assert!(find_algorithm("blake2xb").is_none());
+    }
+}
@@ -0,0 +1,156 @@
+// Runs the checksum binary against manifests in a scratch directory
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+// CRC-32 of "abc"
+const ABC_CRC32: &str = "352441c2";
+
+// A fresh directory per test, so tests can run in parallel
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("checksum-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("abc.txt"), "abc").unwrap();
+    dir
+}
+
+fn checksum(dir: &PathBuf, args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_checksum"))
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn hashes_files_and_stdin() {
+    let dir = scratch_dir("hash");
+    let output = checksum(&dir, &["crc32", "abc.txt", "-"], "abc");
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        format!("{0}  abc.txt\n{0}  -\n", ABC_CRC32)
+    );
+}
+
+#[test]
+fn check_ok() {
+    let dir = scratch_dir("ok");
+    fs::write(
+        dir.join("sums"),
+        format!(
+            "{}  abc.txt\n{} *abc.txt\r\n",
+            ABC_CRC32,
+            ABC_CRC32.to_uppercase()
+        ),
+    )
+    .unwrap();
+    let output = checksum(&dir, &["crc32", "--check", "sums"], "");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "abc.txt: OK\nabc.txt: OK\n");
+    assert_eq!(stderr(&output), "");
+}
+
+#[test]
+fn check_failed() {
+    let dir = scratch_dir("failed");
+    fs::write(
+        dir.join("sums"),
+        format!("00000000  abc.txt\n{}  abc.txt\n", ABC_CRC32),
+    )
+    .unwrap();
+    let output = checksum(&dir, &["crc32", "-c", "sums"], "");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "abc.txt: FAILED\nabc.txt: OK\n");
+    assert!(stderr(&output).contains("1 computed checksum(s) did NOT match"));
+}
+
+#[test]
+fn check_malformed_lines() {
+    let dir = scratch_dir("malformed");
+    fs::write(
+        dir.join("sums"),
+        format!("not a checksum line\n{}  abc.txt\n", ABC_CRC32),
+    )
+    .unwrap();
+    let output = checksum(&dir, &["crc32", "-c", "sums"], "");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "abc.txt: OK\n");
+    assert!(stderr(&output).contains("1 line(s) improperly formatted"));
+
+    // A manifest with no valid line at all is an error
+    fs::write(dir.join("empty"), "not a checksum line\n").unwrap();
+    let output = checksum(&dir, &["crc32", "-c", "empty"], "");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("no properly formatted checksum lines found"));
+}
+
+#[test]
+fn check_unreadable_file() {
+    let dir = scratch_dir("unreadable");
+    fs::write(
+        dir.join("sums"),
+        format!("{}  missing.txt\n{}  abc.txt\n", ABC_CRC32, ABC_CRC32),
+    )
+    .unwrap();
+    let output = checksum(&dir, &["crc32", "-c", "sums"], "");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout(&output),
+        "missing.txt: FAILED open or read\nabc.txt: OK\n"
+    );
+    assert!(stderr(&output).contains("1 listed file(s) could not be read"));
+
+    let output = checksum(&dir, &["crc32", "-c", "missing-sums"], "");
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn check_manifest_from_stdin() {
+    let dir = scratch_dir("stdin");
+    let output = checksum(&dir, &["crc32", "-c"], &format!("{}  abc.txt\n", ABC_CRC32));
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "abc.txt: OK\n");
+
+    // Standard input can't be both the manifest and a listed file
+    let output = checksum(&dir, &["crc32", "-c"], &format!("{}  -\n", ABC_CRC32));
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "-: FAILED open or read\n");
+}
+
+#[test]
+fn unknown_algorithm_and_option() {
+    let dir = scratch_dir("usage");
+    assert_eq!(
+        checksum(&dir, &["md5", "abc.txt"], "").status.code(),
+        Some(1)
+    );
+    assert_eq!(
+        checksum(&dir, &["crc32", "--quiet", "abc.txt"], "")
+            .status
+            .code(),
+        Some(1)
+    );
+    assert_eq!(checksum(&dir, &[], "").status.code(), Some(1));
+}
@@ -1,42 +1,710 @@
-struct CRC32Cipher;
+use crate::hasher::Hasher;
+
+const POLYNOMIAL: u32 = 0xEDB88320;
+
+// Lookup tables for slicing-by-8; CRC32_TABLES[0] is the classic 256-entry table
+const CRC32_TABLES: [[u32; 256]; 8] = make_tables();
+
+const fn make_tables() -> [[u32; 256]; 8] {
+    let mut tables = [[0u32; 256]; 8];
+
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (POLYNOMIAL & mask);
+            bit += 1;
+        }
+        tables[0][i] = crc;
+        i += 1;
+    }
+
+    // tables[k][i] is the CRC of byte i followed by k zero bytes
+    let mut k = 1;
+    while k < 8 {
+        let mut i = 0;
+        while i < 256 {
+            let prev = tables[k - 1][i];
+            tables[k][i] = (prev >> 8) ^ tables[0][(prev & 0xFF) as usize];
+            i += 1;
+        }
+        k += 1;
+    }
+
+    tables
+}
+
+// Rocksoft model CRC parameters ("A Painless Guide to CRC Error Detection Algorithms")
+#[derive(Clone, Copy)]
+pub struct CRCParams {
+    pub name: &'static str,
+    pub width: u32,
+    pub poly: u64,
+    pub init: u64,
+    pub refin: bool,
+    pub refout: bool,
+    pub xorout: u64,
+    pub check: u64, // CRC of "123456789"
+}
+
+pub const CRC_8_SMBUS: CRCParams = CRCParams {
+    name: "CRC-8/SMBUS",
+    width: 8,
+    poly: 0x07,
+    init: 0x00,
+    refin: false,
+    refout: false,
+    xorout: 0x00,
+    check: 0xF4,
+};
+
+pub const CRC_8_MAXIM: CRCParams = CRCParams {
+    name: "CRC-8/MAXIM-DOW",
+    width: 8,
+    poly: 0x31,
+    init: 0x00,
+    refin: true,
+    refout: true,
+    xorout: 0x00,
+    check: 0xA1,
+};
+
+pub const CRC_16_CCITT_FALSE: CRCParams = CRCParams {
+    name: "CRC-16/CCITT-FALSE",
+    width: 16,
+    poly: 0x1021,
+    init: 0xFFFF,
+    refin: false,
+    refout: false,
+    xorout: 0x0000,
+    check: 0x29B1,
+};
+
+pub const CRC_16_KERMIT: CRCParams = CRCParams {
+    name: "CRC-16/KERMIT",
+    width: 16,
+    poly: 0x1021,
+    init: 0x0000,
+    refin: true,
+    refout: true,
+    xorout: 0x0000,
+    check: 0x2189,
+};
+
+pub const CRC_16_XMODEM: CRCParams = CRCParams {
+    name: "CRC-16/XMODEM",
+    width: 16,
+    poly: 0x1021,
+    init: 0x0000,
+    refin: false,
+    refout: false,
+    xorout: 0x0000,
+    check: 0x31C3,
+};
+
+pub const CRC_16_MODBUS: CRCParams = CRCParams {
+    name: "CRC-16/MODBUS",
+    width: 16,
+    poly: 0x8005,
+    init: 0xFFFF,
+    refin: true,
+    refout: true,
+    xorout: 0x0000,
+    check: 0x4B37,
+};
+
+pub const CRC_16_ARC: CRCParams = CRCParams {
+    name: "CRC-16/ARC",
+    width: 16,
+    poly: 0x8005,
+    init: 0x0000,
+    refin: true,
+    refout: true,
+    xorout: 0x0000,
+    check: 0xBB3D,
+};
+
+pub const CRC_24_OPENPGP: CRCParams = CRCParams {
+    name: "CRC-24/OPENPGP",
+    width: 24,
+    poly: 0x864CFB,
+    init: 0xB704CE,
+    refin: false,
+    refout: false,
+    xorout: 0x000000,
+    check: 0x21CF02,
+};
+
+pub const CRC_32_ISO_HDLC: CRCParams = CRCParams {
+    name: "CRC-32/ISO-HDLC",
+    width: 32,
+    poly: 0x04C11DB7,
+    init: 0xFFFFFFFF,
+    refin: true,
+    refout: true,
+    xorout: 0xFFFFFFFF,
+    check: 0xCBF43926,
+};
+
+pub const CRC_32_BZIP2: CRCParams = CRCParams {
+    name: "CRC-32/BZIP2",
+    width: 32,
+    poly: 0x04C11DB7,
+    init: 0xFFFFFFFF,
+    refin: false,
+    refout: false,
+    xorout: 0xFFFFFFFF,
+    check: 0xFC891918,
+};
+
+pub const CRC_32_MPEG2: CRCParams = CRCParams {
+    name: "CRC-32/MPEG-2",
+    width: 32,
+    poly: 0x04C11DB7,
+    init: 0xFFFFFFFF,
+    refin: false,
+    refout: false,
+    xorout: 0x00000000,
+    check: 0x0376E6E7,
+};
+
+pub const CRC_32C: CRCParams = CRCParams {
+    name: "CRC-32C",
+    width: 32,
+    poly: 0x1EDC6F41,
+    init: 0xFFFFFFFF,
+    refin: true,
+    refout: true,
+    xorout: 0xFFFFFFFF,
+    check: 0xE3069283,
+};
+
+pub const CRC_64_XZ: CRCParams = CRCParams {
+    name: "CRC-64/XZ",
+    width: 64,
+    poly: 0x42F0E1EBA9EA3693,
+    init: 0xFFFFFFFFFFFFFFFF,
+    refin: true,
+    refout: true,
+    xorout: 0xFFFFFFFFFFFFFFFF,
+    check: 0x995DC9BBDF1939FA,
+};
+
+pub const CRC_64_ECMA_182: CRCParams = CRCParams {
+    name: "CRC-64/ECMA-182",
+    width: 64,
+    poly: 0x42F0E1EBA9EA3693,
+    init: 0x0000000000000000,
+    refin: false,
+    refout: false,
+    xorout: 0x0000000000000000,
+    check: 0x6C40DF5F0B497347,
+};
+
+pub const CRC_CATALOGUE: [CRCParams; 14] = [
+    CRC_8_SMBUS,
+    CRC_8_MAXIM,
+    CRC_16_CCITT_FALSE,
+    CRC_16_KERMIT,
+    CRC_16_XMODEM,
+    CRC_16_MODBUS,
+    CRC_16_ARC,
+    CRC_24_OPENPGP,
+    CRC_32_ISO_HDLC,
+    CRC_32_BZIP2,
+    CRC_32_MPEG2,
+    CRC_32C,
+    CRC_64_XZ,
+    CRC_64_ECMA_182,
+];
+
+fn reflect(value: u64, width: u32) -> u64 {
+    value.reverse_bits() >> (64 - width)
+}
+
+// Table-driven CRC engine for any width from 8 to 64 bits
+pub struct CRCEngine {
+    params: CRCParams,
+    table: [u64; 256],
+    mask: u64,
+}
+
+impl CRCEngine {
+    pub fn new(params: CRCParams) -> Result<CRCEngine, &'static str> {
+        if params.width < 8 || params.width > 64 {
+            return Err("Invalid CRC width");
+        }
+
+        let mask = u64::MAX >> (64 - params.width);
+        if params.poly & !mask != 0 || params.init & !mask != 0 || params.xorout & !mask != 0 {
+            return Err("Parameter wider than CRC width");
+        }
+
+        let mut table = [0u64; 256];
+        if params.refin {
+            // LSB-first register with the reflected polynomial
+            let poly = reflect(params.poly, params.width);
+            for (i, entry) in table.iter_mut().enumerate() {
+                let mut crc = i as u64;
+                for _ in 0..8 {
+                    crc = if crc & 1 == 1 { (crc >> 1) ^ poly } else { crc >> 1 };
+                }
+                *entry = crc;
+            }
+        } else {
+            // MSB-first register, each byte entering at the top
+            let top = 1u64 << (params.width - 1);
+            for (i, entry) in table.iter_mut().enumerate() {
+                let mut crc = (i as u64) << (params.width - 8);
+                for _ in 0..8 {
+                    crc = if crc & top != 0 { (crc << 1) ^ params.poly } else { crc << 1 };
+                }
+                *entry = crc & mask;
+            }
+        }
+
+        Ok(CRCEngine { params, table, mask })
+    }
+
+    pub fn checksum(&self, input: &[u8]) -> u64 {
+        self.finish(self.absorb(self.start(), input))
+    }
+
+    // Register contents before any input, in the engine's bit order
+    fn start(&self) -> u64 {
+        if self.params.refin {
+            reflect(self.params.init, self.params.width)
+        } else {
+            self.params.init
+        }
+    }
+
+    fn absorb(&self, mut crc: u64, input: &[u8]) -> u64 {
+        let width = self.params.width;
+        if self.params.refin {
+            for &byte in input {
+                crc = (crc >> 8) ^ self.table[((crc ^ byte as u64) & 0xFF) as usize];
+            }
+        } else {
+            for &byte in input {
+                let index = ((crc >> (width - 8)) ^ byte as u64) & 0xFF;
+                crc = ((crc << 8) ^ self.table[index as usize]) & self.mask;
+            }
+        }
+        crc
+    }
+
+    fn finish(&self, mut crc: u64) -> u64 {
+        if self.params.refin != self.params.refout {
+            crc = reflect(crc, self.params.width);
+        }
+
+        (crc ^ self.params.xorout) & self.mask
+    }
+}
+
+// Incremental CRC over byte slices for any engine in the catalogue
+pub struct CRCState {
+    engine: CRCEngine,
+    crc: u64,
+}
+
+impl CRCState {
+    pub fn new(params: CRCParams) -> Result<CRCState, &'static str> {
+        let engine = CRCEngine::new(params)?;
+        let crc = engine.start();
+        Ok(CRCState { engine, crc })
+    }
+}
+
+impl Hasher for CRCState {
+    type Output = u64;
+
+    fn update(&mut self, data: &[u8]) {
+        self.crc = self.engine.absorb(self.crc, data);
+    }
+
+    fn finalize(&mut self) -> u64 {
+        self.engine.finish(self.crc)
+    }
+
+    fn reset(&mut self) {
+        self.crc = self.engine.start();
+    }
+}
+
+impl_io_write!(CRCState);
+
+#[derive(Default)]
+pub struct CRC32Cipher;
 
 impl CRC32Cipher {
-    fn new() -> CRC32Cipher {
+    pub fn new() -> CRC32Cipher {
         CRC32Cipher
     }
 
-    fn encrypt(&self, input: &str) -> u32 {
+    pub fn encrypt(&self, input: impl AsRef<[u8]>) -> u32 {
         let mut crc = 0xFFFFFFFFu32;
-        for byte in input.bytes() {
+        for &byte in input.as_ref() {
             crc ^= byte as u32;
             for _ in 0..8 {
                 let mask = (crc & 1).wrapping_neg();
                 crc = (crc >> 1) ^ (0xEDB88320u32 & mask);
             }
         }
-        (!crc) & 0xFFFFFFFFu32
+        !crc
+    }
+
+    // One table lookup per byte instead of eight shift/xor steps
+    pub fn encrypt_table(&self, input: impl AsRef<[u8]>) -> u32 {
+        let mut crc = 0xFFFFFFFFu32;
+        for &byte in input.as_ref() {
+            crc = (crc >> 8) ^ CRC32_TABLES[0][((crc ^ byte as u32) & 0xFF) as usize];
+        }
+        !crc
+    }
+
+    pub fn encrypt_slice8(&self, input: impl AsRef<[u8]>) -> u32 {
+        !slice8_update(0xFFFFFFFF, input.as_ref())
     }
 }
 
-fn test_crc32() {
-    let cipher = CRC32Cipher::new();
-    let test_cases = [
+// Processes eight bytes per step using eight independent table lookups.
+// 'crc' is the raw (pre-inverted) register.
+fn slice8_update(mut crc: u32, bytes: &[u8]) -> u32 {
+    let mut chunks = bytes.chunks_exact(8);
+    for chunk in &mut chunks {
+        let lo = crc ^ u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        let hi = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+        crc = CRC32_TABLES[7][(lo & 0xFF) as usize]
+            ^ CRC32_TABLES[6][((lo >> 8) & 0xFF) as usize]
+            ^ CRC32_TABLES[5][((lo >> 16) & 0xFF) as usize]
+            ^ CRC32_TABLES[4][(lo >> 24) as usize]
+            ^ CRC32_TABLES[3][(hi & 0xFF) as usize]
+            ^ CRC32_TABLES[2][((hi >> 8) & 0xFF) as usize]
+            ^ CRC32_TABLES[1][((hi >> 16) & 0xFF) as usize]
+            ^ CRC32_TABLES[0][(hi >> 24) as usize];
+    }
+
+    for &byte in chunks.remainder() {
+        crc = (crc >> 8) ^ CRC32_TABLES[0][((crc ^ byte as u32) & 0xFF) as usize];
+    }
+
+    crc
+}
+
+// Incremental CRC-32 over byte slices
+pub struct CRC32State {
+    crc: u32,
+    len: u64,
+}
+
+impl CRC32State {
+    pub fn new() -> CRC32State {
+        CRC32State {
+            crc: 0xFFFFFFFF,
+            len: 0,
+        }
+    }
+
+    // Bytes absorbed since construction or the last reset; the 'len_b' that
+    // crc32_combine needs when this state's CRC is appended to another
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Default for CRC32State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for CRC32State {
+    type Output = u32;
+
+    fn update(&mut self, data: &[u8]) {
+        self.crc = slice8_update(self.crc, data);
+        self.len += data.len() as u64;
+    }
+
+    fn finalize(&mut self) -> u32 {
+        !self.crc
+    }
+
+    fn reset(&mut self) {
+        *self = CRC32State::new();
+    }
+}
+
+impl_io_write!(CRC32State);
+
+fn gf2_matrix_times(mat: &[u32; 32], mut vec: u32) -> u32 {
+    let mut sum = 0;
+    let mut i = 0;
+    while vec != 0 {
+        if vec & 1 != 0 {
+            sum ^= mat[i];
+        }
+        vec >>= 1;
+        i += 1;
+    }
+    sum
+}
+
+fn gf2_matrix_square(square: &mut [u32; 32], mat: &[u32; 32]) {
+    for n in 0..32 {
+        square[n] = gf2_matrix_times(mat, mat[n]);
+    }
+}
+
+// CRC-32 of A || B given crc(A), crc(B) and len(B), as in zlib's crc32_combine.
+// Appending len_b zero bytes to A is applied as a GF(2) matrix raised to
+// len_b by repeated squaring, so this runs in O(log len_b).
+pub fn crc32_combine(mut crc_a: u32, crc_b: u32, mut len_b: u64) -> u32 {
+    if len_b == 0 {
+        return crc_a;
+    }
+
+    let mut even = [0u32; 32]; // operator for 2^n zero bits, n even
+    let mut odd = [0u32; 32]; // operator for 2^n zero bits, n odd
+
+    // Operator for a single zero bit
+    odd[0] = POLYNOMIAL;
+    let mut row = 1u32;
+    for entry in odd.iter_mut().skip(1) {
+        *entry = row;
+        row <<= 1;
+    }
+
+    gf2_matrix_square(&mut even, &odd); // two zero bits
+    gf2_matrix_square(&mut odd, &even); // four zero bits
+
+    // The first squaring below gives one zero byte (eight zero bits)
+    loop {
+        gf2_matrix_square(&mut even, &odd);
+        if len_b & 1 != 0 {
+            crc_a = gf2_matrix_times(&even, crc_a);
+        }
+        len_b >>= 1;
+        if len_b == 0 {
+            break;
+        }
+
+        gf2_matrix_square(&mut odd, &even);
+        if len_b & 1 != 0 {
+            crc_a = gf2_matrix_times(&odd, crc_a);
+        }
+        len_b >>= 1;
+        if len_b == 0 {
+            break;
+        }
+    }
+
+    crc_a ^ crc_b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hasher::digest_chunked;
+    use std::io::{self, Cursor};
+    use std::time::Instant;
+
+    // Reference values from zlib's crc32()
+    const KNOWN_ANSWERS: [(&str, u32); 8] = [
+        ("", 0x00000000),
+        ("a", 0xE8B7BE43),
+        ("abc", 0x352441C2),
+        ("123456789", 0xCBF43926),
+        ("message digest", 0x20159D7F),
+        ("abcdefghijklmnopqrstuvwxyz", 0x4C2750BD),
         ("Hello, world!", 0xEBE6C6E6),
         ("The quick brown fox jumps over the lazy dog", 0x414FA339),
-        ("", 0x00000000),
     ];
 
-    for (i, (input, expected)) in test_cases.iter().enumerate() {
-        let output = cipher.encrypt(input);
-        let status = if output == *expected { "PASSED" } else { "FAILED" };
-        println!("Test {}: {}", i + 1, status);
-        println!("Input:    '{}'", input);
-        println!("Expected: '{:08X}'", expected);
-        println!("Output:   '{:08X}'", output);
-        println!();
+    #[test]
+    fn known_answers() {
+        let cipher = CRC32Cipher::new();
+        for (input, expected) in KNOWN_ANSWERS {
+            assert_eq!(cipher.encrypt(input), expected, "input {:?}", input);
+            assert_eq!(cipher.encrypt_table(input), expected, "input {:?}", input);
+            assert_eq!(cipher.encrypt_slice8(input), expected, "input {:?}", input);
+            assert_eq!(CRC32State::new().digest(input.as_bytes()), expected, "input {:?}", input);
+        }
+        assert_eq!(cipher.encrypt_slice8(vec![b'a'; 1_000_000]), 0xDC25BFBC);
+    }
+
+    #[test]
+    fn fast_paths_match_bitwise() {
+        let cipher = CRC32Cipher::new();
+
+        // Lengths around the 8-byte stride, filled with a simple LCG
+        let mut state = 12345u32;
+        for len in 0..40 {
+            let input: Vec<u8> = (0..len)
+                .map(|_| {
+                    state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                    (state >> 16) as u8
+                })
+                .collect();
+
+            let expected = cipher.encrypt(&input);
+            assert_eq!(cipher.encrypt_table(&input), expected, "length {}", len);
+            assert_eq!(cipher.encrypt_slice8(&input), expected, "length {}", len);
+        }
+    }
+
+    #[test]
+    fn binary_input() {
+        let cipher = CRC32Cipher::new();
+        // Every byte value, so not valid UTF-8
+        let data: Vec<u8> = (0..=255).collect();
+        let expected = 0x29058C73;
+
+        assert_eq!(cipher.encrypt(&data), expected);
+        assert_eq!(cipher.encrypt_table(&data[..]), expected);
+        assert_eq!(cipher.encrypt_slice8(data.as_slice()), expected);
+
+        let mut state = CRC32State::new();
+        assert_eq!(state.digest_reader(Cursor::new(&data)).unwrap(), expected);
+
+        // Larger than the reader's internal buffer
+        let big: Vec<u8> = (0..200_000u32).map(|i| (i * 7 + i / 256) as u8).collect();
+        let mut state = CRC32State::new();
+        let copied = io::copy(&mut Cursor::new(&big), &mut state).unwrap();
+        assert_eq!(copied, big.len() as u64);
+        assert_eq!(state.finalize(), cipher.encrypt_slice8(&big));
+    }
+
+    #[test]
+    fn streaming_matches_one_shot() {
+        let cipher = CRC32Cipher::new();
+        let input = "The quick brown fox jumps over the lazy dog";
+
+        for chunk_size in 1..=input.len() {
+            let mut state = CRC32State::new();
+            for chunk in input.as_bytes().chunks(chunk_size) {
+                state.update(chunk);
+            }
+            assert_eq!(state.finalize(), cipher.encrypt(input), "chunk size {}", chunk_size);
+            assert_eq!(state.len(), input.len() as u64);
+        }
+    }
+
+    #[test]
+    fn hasher_digest_and_reset() {
+        let input = b"The quick brown fox jumps over the lazy dog";
+        let mut state = CRC32State::new();
+        state.update(b"discarded by reset");
+        assert_eq!(state.digest(input), 0x414FA339);
+        assert_eq!(digest_chunked(&mut state, input, 5), 0x414FA339);
+        assert_eq!(state.len(), input.len() as u64);
+    }
+
+    #[test]
+    fn combine_at_every_split() {
+        let cipher = CRC32Cipher::new();
+        let input = "The quick brown fox jumps over the lazy dog";
+
+        // Including empty halves
+        for split in 0..=input.len() {
+            let (a, b) = input.split_at(split);
+            let combined = crc32_combine(cipher.encrypt(a), cipher.encrypt(b), b.len() as u64);
+            assert_eq!(combined, cipher.encrypt(input), "split at {}", split);
+        }
     }
-}
 
-fn main() {
-    test_crc32();
+    #[test]
+    fn combine_over_chunks() {
+        let cipher = CRC32Cipher::new();
+
+        // Checksums of parallel chunks merged left to right
+        let data: String = (0..100_000).map(|i| char::from(b'a' + (i * 7 % 26) as u8)).collect();
+        let mut crc = 0;
+        for chunk in data.as_bytes().chunks(4096 + 17) {
+            let mut state = CRC32State::new();
+            assert!(state.is_empty());
+            state.update(chunk);
+            crc = crc32_combine(crc, state.finalize(), state.len());
+        }
+        assert_eq!(crc, cipher.encrypt_slice8(&data));
+    }
+
+    #[test]
+    fn catalogue_check_values() {
+        for params in CRC_CATALOGUE.iter() {
+            let engine = CRCEngine::new(*params).unwrap();
+            assert_eq!(engine.checksum(b"123456789"), params.check, "{}", params.name);
+        }
+    }
+
+    #[test]
+    fn catalogue_streaming_matches_one_shot() {
+        let input = b"The quick brown fox jumps over the lazy dog";
+        for params in CRC_CATALOGUE.iter() {
+            let engine = CRCEngine::new(*params).unwrap();
+            let mut state = CRCState::new(*params).unwrap();
+            assert_eq!(state.digest(b"123456789"), params.check, "{}", params.name);
+            for chunk_size in [1, 3, 8, 43] {
+                let streamed = digest_chunked(&mut state, input, chunk_size);
+                assert_eq!(streamed, engine.checksum(input), "{} chunk size {}", params.name, chunk_size);
+            }
+
+            state.reset();
+            io::copy(&mut Cursor::new(&input[..]), &mut state).unwrap();
+            assert_eq!(state.finalize(), engine.checksum(input), "{}", params.name);
+        }
+    }
+
+    #[test]
+    fn iso_hdlc_matches_crc32_cipher() {
+        let cipher = CRC32Cipher::new();
+        let engine = CRCEngine::new(CRC_32_ISO_HDLC).unwrap();
+        for (input, _) in KNOWN_ANSWERS {
+            assert_eq!(engine.checksum(input.as_bytes()), cipher.encrypt(input) as u64, "input {:?}", input);
+        }
+    }
+
+    #[test]
+    fn invalid_width_rejected() {
+        let invalid = CRCParams { width: 7, ..CRC_8_SMBUS };
+        assert!(CRCEngine::new(invalid).is_err());
+        assert!(CRCState::new(invalid).is_err());
+    }
+
+    // Timing only; run with `cargo test --release -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_crc32() {
+        let cipher = CRC32Cipher::new();
+        let input: String = (0..16 * 1024 * 1024).map(|i| char::from(b'a' + (i % 26) as u8)).collect();
+        let mb = input.len() as f64 / (1024.0 * 1024.0);
+
+        type CrcFn = fn(&CRC32Cipher, &str) -> u32;
+        let approaches: [(&str, CrcFn); 3] = [
+            ("bitwise", |cipher, input| cipher.encrypt(input)),
+            ("table", |cipher, input| cipher.encrypt_table(input)),
+            ("slicing-by-8", |cipher, input| cipher.encrypt_slice8(input)),
+        ];
+
+        for (name, f) in approaches.iter() {
+            let start = Instant::now();
+            let crc = f(&cipher, &input);
+            let elapsed = start.elapsed().as_secs_f64();
+            println!(
+                "{:>12}: {:08X} in {:.3}s ({:.1} MB/s)",
+                name,
+                crc,
+                elapsed,
+                mb / elapsed
+            );
+        }
+    }
 }
@@ -1,38 +1,379 @@
-struct DJB2Cipher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hasher};
+
+use crate::hasher::Hasher as _;
+
+// Shared interface for the simple multiplicative/additive string hashes
+pub trait StringHash {
+    fn hash_bytes(&self, input: &[u8]) -> u32;
+
+    fn hash_str(&self, input: &str) -> u32 {
+        self.hash_bytes(input.as_bytes())
+    }
+}
+
+pub struct DJB2Cipher {
+    hash_value: u32,
+}
 
 impl DJB2Cipher {
-    fn new() -> DJB2Cipher {
-        DJB2Cipher
+    pub fn new() -> DJB2Cipher {
+        DJB2Cipher { hash_value: 5381 }
+    }
+
+    pub fn encrypt(&self, input: impl AsRef<[u8]>) -> u32 {
+        self.hash_bytes(input.as_ref())
+    }
+}
+
+impl StringHash for DJB2Cipher {
+    fn hash_bytes(&self, input: &[u8]) -> u32 {
+        let mut state = DJB2Cipher::new();
+        state.update(input);
+        state.finalize()
     }
+}
+
+impl Default for DJB2Cipher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-    fn encrypt(&self, input: &str) -> u32 {
-        let mut hash = 5381u32;
-        for byte in input.bytes() {
-            hash = (hash.wrapping_mul(33)).wrapping_add(byte as u32);
+// Streaming interface: 'hash_value' holds the running state, hash * 33 + c
+impl Hasher for DJB2Cipher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.hash_value = (self.hash_value.wrapping_mul(33)).wrapping_add(byte as u32);
         }
-        hash & 0xFFFFFFFFu32
+    }
+
+    // HashMap takes its control bits from the top of the 64-bit hash, which a
+    // 32-bit djb2 value leaves empty, so spread it with a Fibonacci multiply
+    fn finish(&self) -> u64 {
+        (self.hash_value as u64).wrapping_mul(0x9E3779B97F4A7C15)
     }
 }
 
-fn test_djb2() {
-    let cipher = DJB2Cipher::new();
-    let test_cases = [
-        ("Hello, world!", 0xE18796AE),
-        ("The quick brown fox jumps over the lazy dog", 0x34CC38DE),
-        ("", 0x1505),
-    ];
+impl crate::hasher::Hasher for DJB2Cipher {
+    type Output = u32;
+
+    fn update(&mut self, data: &[u8]) {
+        self.write(data);
+    }
+
+    fn finalize(&mut self) -> u32 {
+        self.hash_value
+    }
+
+    fn reset(&mut self) {
+        self.hash_value = 5381;
+    }
+}
+
+impl_io_write!(DJB2Cipher);
+
+// Builds djb2 hashers for HashMap and HashSet
+#[derive(Clone, Copy, Default)]
+pub struct DJB2BuildHasher;
+
+impl BuildHasher for DJB2BuildHasher {
+    type Hasher = DJB2Cipher;
+
+    fn build_hasher(&self) -> DJB2Cipher {
+        DJB2Cipher::new()
+    }
+}
+
+pub type DJB2HashMap<K, V> = HashMap<K, V, DJB2BuildHasher>;
+pub type DJB2HashSet<T> = HashSet<T, DJB2BuildHasher>;
+
+// djb2a: hash * 33 ^ c
+pub struct DJB2aCipher {
+    hash_value: u32,
+}
+
+impl DJB2aCipher {
+    pub fn new() -> DJB2aCipher {
+        DJB2aCipher { hash_value: 5381 }
+    }
+}
+
+impl Default for DJB2aCipher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StringHash for DJB2aCipher {
+    fn hash_bytes(&self, input: &[u8]) -> u32 {
+        let mut state = DJB2aCipher::new();
+        state.update(input);
+        state.finalize()
+    }
+}
+
+impl crate::hasher::Hasher for DJB2aCipher {
+    type Output = u32;
+
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.hash_value = self.hash_value.wrapping_mul(33) ^ byte as u32;
+        }
+    }
+
+    fn finalize(&mut self) -> u32 {
+        self.hash_value
+    }
+
+    fn reset(&mut self) {
+        self.hash_value = 5381;
+    }
+}
+
+impl_io_write!(DJB2aCipher);
+
+// sdbm: c + (hash << 6) + (hash << 16) - hash, i.e. hash * 65599 + c
+pub struct SDBMCipher {
+    hash_value: u32,
+}
+
+impl SDBMCipher {
+    pub fn new() -> SDBMCipher {
+        SDBMCipher { hash_value: 0 }
+    }
+}
+
+impl Default for SDBMCipher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StringHash for SDBMCipher {
+    fn hash_bytes(&self, input: &[u8]) -> u32 {
+        let mut state = SDBMCipher::new();
+        state.update(input);
+        state.finalize()
+    }
+}
+
+impl crate::hasher::Hasher for SDBMCipher {
+    type Output = u32;
+
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.hash_value = (byte as u32)
+                .wrapping_add(self.hash_value << 6)
+                .wrapping_add(self.hash_value << 16)
+                .wrapping_sub(self.hash_value);
+        }
+    }
+
+    fn finalize(&mut self) -> u32 {
+        self.hash_value
+    }
+
+    fn reset(&mut self) {
+        self.hash_value = 0;
+    }
+}
+
+impl_io_write!(SDBMCipher);
+
+// K&R "lose lose": the plain sum of the bytes, kept as a bad-distribution baseline
+pub struct LoseLoseCipher {
+    hash_value: u32,
+}
+
+impl LoseLoseCipher {
+    pub fn new() -> LoseLoseCipher {
+        LoseLoseCipher { hash_value: 0 }
+    }
+}
+
+impl Default for LoseLoseCipher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StringHash for LoseLoseCipher {
+    fn hash_bytes(&self, input: &[u8]) -> u32 {
+        let mut state = LoseLoseCipher::new();
+        state.update(input);
+        state.finalize()
+    }
+}
+
+impl crate::hasher::Hasher for LoseLoseCipher {
+    type Output = u32;
 
-    for (i, (input, expected)) in test_cases.iter().enumerate() {
-        let output = cipher.encrypt(input);
-        let status = if output == *expected { "PASSED" } else { "FAILED" };
-        println!("Test {}: {}", i + 1, status);
-        println!("Input:    '{}'", input);
-        println!("Expected: '{:X}'", expected);
-        println!("Output:   '{:X}'", output);
-        println!();
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.hash_value = self.hash_value.wrapping_add(byte as u32);
+        }
+    }
+
+    fn finalize(&mut self) -> u32 {
+        self.hash_value
+    }
+
+    fn reset(&mut self) {
+        self.hash_value = 0;
     }
 }
 
-fn main() {
-    test_djb2();
+impl_io_write!(LoseLoseCipher);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hasher::digest_chunked;
+    use std::io::{self, Cursor};
+
+    // Number of keys landing in each of 'buckets' buckets
+    fn bucket_counts(hasher: &dyn StringHash, keys: &[&[u8]], buckets: usize) -> Vec<usize> {
+        let mut counts = vec![0; buckets];
+        for key in keys {
+            counts[hasher.hash_bytes(key) as usize % buckets] += 1;
+        }
+        counts
+    }
+
+    // Pearson's chi-square statistic against a uniform spread; close to
+    // buckets - 1 for a well-distributed hash
+    fn chi_square(counts: &[usize]) -> f64 {
+        let total: usize = counts.iter().sum();
+        let expected = total as f64 / counts.len() as f64;
+        counts
+            .iter()
+            .map(|&count| (count as f64 - expected).powi(2) / expected)
+            .sum()
+    }
+
+    // (input, djb2, djb2a, sdbm, lose lose), checked against an independent
+    // implementation of each recurrence
+    const KNOWN_ANSWERS: [(&str, u32, u32, u32, u32); 6] = [
+        ("", 0x1505, 0x1505, 0x0, 0x0),
+        ("a", 0x2B606, 0x2B5C4, 0x61, 0x61),
+        ("abc", 0xB885C8B, 0xB873285, 0x3025F862, 0x126),
+        ("hello", 0xF923099, 0xA9CEDE7, 0x28D19932, 0x214),
+        ("Hello, world!", 0xE18796AE, 0x564369E8, 0xCF856FF5, 0x489),
+        ("The quick brown fox jumps over the lazy dog", 0x34CC38DE, 0xB679B80A, 0x8CA77173, 0xFD9),
+    ];
+
+    #[test]
+    fn known_answers() {
+        for (input, djb2, djb2a, sdbm, lose_lose) in KNOWN_ANSWERS {
+            assert_eq!(DJB2Cipher::new().encrypt(input), djb2, "djb2 {:?}", input);
+            assert_eq!(DJB2Cipher::new().hash_str(input), djb2, "djb2 {:?}", input);
+            assert_eq!(DJB2aCipher::new().hash_str(input), djb2a, "djb2a {:?}", input);
+            assert_eq!(SDBMCipher::new().hash_str(input), sdbm, "sdbm {:?}", input);
+            assert_eq!(LoseLoseCipher::new().hash_bytes(input.as_bytes()), lose_lose, "lose lose {:?}", input);
+        }
+    }
+
+    #[test]
+    fn streaming_matches_one_shot() {
+        // The streaming Hasher trait, fed in 3-byte chunks
+        for (input, djb2, djb2a, sdbm, lose_lose) in KNOWN_ANSWERS {
+            let bytes = input.as_bytes();
+            assert_eq!(digest_chunked(&mut DJB2Cipher::new(), bytes, 3), djb2, "djb2 {:?}", input);
+            assert_eq!(digest_chunked(&mut DJB2aCipher::new(), bytes, 3), djb2a, "djb2a {:?}", input);
+            assert_eq!(digest_chunked(&mut SDBMCipher::new(), bytes, 3), sdbm, "sdbm {:?}", input);
+            assert_eq!(digest_chunked(&mut LoseLoseCipher::new(), bytes, 3), lose_lose, "lose lose {:?}", input);
+        }
+    }
+
+    #[test]
+    fn hasher_reset() {
+        // digest() resets first, so earlier input does not leak into the result
+        let mut sdbm = SDBMCipher::new();
+        sdbm.update(b"stale");
+        assert_eq!(sdbm.digest(b"Hello, world!"), 0xCF856FF5);
+
+        let mut djb2 = DJB2Cipher::new();
+        djb2.update(b"stale");
+        assert_eq!(djb2.digest(b"Hello, world!"), 0xE18796AE);
+    }
+
+    #[test]
+    fn std_hasher_and_collections() {
+        let mut hasher = DJB2BuildHasher.build_hasher();
+        hasher.write(b"Hello, ");
+        hasher.write(b"world!");
+        assert_eq!(hasher.hash_value, 0xE18796AE);
+        assert_eq!(hasher.finish(), (0xE18796AEu64).wrapping_mul(0x9E3779B97F4A7C15));
+
+        let mut map: DJB2HashMap<&str, i32> = DJB2HashMap::default();
+        map.insert("one", 1);
+        map.insert("two", 2);
+        map.insert("three", 3);
+        assert_eq!(map.get("two"), Some(&2));
+        assert_eq!(map.len(), 3);
+
+        let mut set: DJB2HashSet<u64> = DJB2HashSet::default();
+        for i in 0..1000 {
+            set.insert(i * 7);
+        }
+        assert!(set.contains(&693));
+        assert!(!set.contains(&694));
+    }
+
+    #[test]
+    fn binary_input() {
+        let cipher = DJB2Cipher::new();
+        // Every byte value, so not valid UTF-8
+        let data: Vec<u8> = (0..=255).collect();
+        let expected = 0x9A5B9485;
+
+        assert_eq!(cipher.encrypt(&data), expected);
+        assert_eq!(cipher.hash_bytes(&data), expected);
+
+        let mut streaming = DJB2Cipher::new();
+        assert_eq!(streaming.digest_reader(Cursor::new(&data)).unwrap(), expected);
+
+        let big: Vec<u8> = (0..200_000u32).map(|i| (i * 7 + i / 256) as u8).collect();
+        let mut sdbm = SDBMCipher::new();
+        io::copy(&mut Cursor::new(&big), &mut sdbm).unwrap();
+        assert_eq!(sdbm.finalize(), SDBMCipher::new().hash_bytes(&big));
+    }
+
+    // Report only; run with `cargo test --release -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn compare_distribution() {
+        let keys: Vec<String> = (0..100_000).map(|i| format!("user:{}", i)).collect();
+        let keys: Vec<&[u8]> = keys.iter().map(|key| key.as_bytes()).collect();
+        let buckets = 1024;
+
+        let hashes: [(&str, &dyn StringHash); 4] = [
+            ("djb2", &DJB2Cipher::new()),
+            ("djb2a", &DJB2aCipher::new()),
+            ("sdbm", &SDBMCipher::new()),
+            ("lose lose", &LoseLoseCipher::new()),
+        ];
+
+        println!("{} keys into {} buckets (ideal chi-square ~{}):", keys.len(), buckets, buckets - 1);
+        for (name, hasher) in hashes.iter() {
+            let counts = bucket_counts(*hasher, &keys, buckets);
+            let max = counts.iter().max().unwrap();
+            let empty = counts.iter().filter(|&&count| count == 0).count();
+            println!(
+                "  {:>9}: chi-square {:>12.1}, max bucket {:>6}, empty buckets {:>4}",
+                name,
+                chi_square(&counts),
+                max,
+                empty
+            );
+        }
+    }
+
+    // Timing only; run with `cargo test --release -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_djb2_hashmap() {
+        crate::hasher::bench_hashmap::<DJB2BuildHasher>("djb2");
+    }
 }
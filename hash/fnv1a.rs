@@ -1,41 +1,522 @@
-struct FNV1a {
-    prime: u32,
-    hash_value: u32,
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hasher};
+
+use crate::hasher::Hasher as _;
+
+// Word arithmetic FNV needs at each width: XOR a byte into the low bits and
+// multiply modulo 2^bits
+pub trait FNVWord: Copy + PartialEq {
+    fn fnv_prime() -> Self;
+    fn fnv_offset_basis() -> Self;
+    fn xor_byte(self, byte: u8) -> Self;
+    fn mul_mod(self, rhs: Self) -> Self;
+    fn to_hex(self) -> String;
+    // Value reported by std::hash::Hasher::finish
+    fn finish_u64(self) -> u64;
+}
+
+impl FNVWord for u32 {
+    fn fnv_prime() -> Self {
+        0x01000193
+    }
+
+    fn fnv_offset_basis() -> Self {
+        0x811c9dc5
+    }
+
+    fn xor_byte(self, byte: u8) -> Self {
+        self ^ byte as u32
+    }
+
+    fn mul_mod(self, rhs: Self) -> Self {
+        self.wrapping_mul(rhs)
+    }
+
+    fn to_hex(self) -> String {
+        format!("{:08x}", self)
+    }
+
+    // HashMap takes its control bits from the top of the 64-bit hash, which a
+    // 32-bit value leaves empty, so spread it with a Fibonacci multiply
+    fn finish_u64(self) -> u64 {
+        (self as u64).wrapping_mul(0x9E3779B97F4A7C15)
+    }
+}
+
+impl FNVWord for u64 {
+    fn fnv_prime() -> Self {
+        0x00000100000001b3
+    }
+
+    fn fnv_offset_basis() -> Self {
+        0xcbf29ce484222325
+    }
+
+    fn xor_byte(self, byte: u8) -> Self {
+        self ^ byte as u64
+    }
+
+    fn mul_mod(self, rhs: Self) -> Self {
+        self.wrapping_mul(rhs)
+    }
+
+    fn to_hex(self) -> String {
+        format!("{:016x}", self)
+    }
+
+    fn finish_u64(self) -> u64 {
+        self
+    }
 }
 
-impl FNV1a {
-    fn new() -> FNV1a {
+impl FNVWord for u128 {
+    fn fnv_prime() -> Self {
+        0x0000000001000000000000000000013b
+    }
+
+    fn fnv_offset_basis() -> Self {
+        0x6c62272e07bb014262b821756295c58d
+    }
+
+    fn xor_byte(self, byte: u8) -> Self {
+        self ^ byte as u128
+    }
+
+    fn mul_mod(self, rhs: Self) -> Self {
+        self.wrapping_mul(rhs)
+    }
+
+    fn to_hex(self) -> String {
+        format!("{:032x}", self)
+    }
+
+    fn finish_u64(self) -> u64 {
+        self as u64
+    }
+}
+
+// Fixed-width unsigned integer stored as little-endian 64-bit limbs
+#[derive(Clone, Copy, PartialEq)]
+pub struct BigUint<const LIMBS: usize>([u64; LIMBS]);
+
+impl<const LIMBS: usize> BigUint<LIMBS> {
+    fn from_hex(hex: &str) -> Self {
+        let mut limbs = [0u64; LIMBS];
+        let digits = hex.as_bytes();
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let end = digits.len().saturating_sub(i * 16);
+            let start = end.saturating_sub(16);
+            if start < end {
+                *limb = u64::from_str_radix(&hex[start..end], 16).unwrap();
+            }
+        }
+        BigUint(limbs)
+    }
+
+    // Schoolbook multiplication, dropping every limb above the width
+    fn wrapping_mul(&self, rhs: &Self) -> Self {
+        let mut result = [0u64; LIMBS];
+        for i in 0..LIMBS {
+            if self.0[i] == 0 {
+                continue;
+            }
+            let mut carry = 0u128;
+            for j in 0..LIMBS - i {
+                let t = self.0[i] as u128 * rhs.0[j] as u128 + result[i + j] as u128 + carry;
+                result[i + j] = t as u64;
+                carry = t >> 64;
+            }
+        }
+        BigUint(result)
+    }
+}
+
+pub type U256 = BigUint<4>;
+pub type U512 = BigUint<8>;
+pub type U1024 = BigUint<16>;
+
+// Only the widths FNV defines parameters for get an FNVWord impl, so an
+// unsupported BigUint width is a compile error
+macro_rules! impl_fnv_word_for_biguint {
+    ($word:ty, $prime:expr, $offset_basis:expr) => {
+        impl FNVWord for $word {
+            fn fnv_prime() -> Self {
+                Self::from_hex($prime)
+            }
+
+            fn fnv_offset_basis() -> Self {
+                Self::from_hex($offset_basis)
+            }
+
+            fn xor_byte(mut self, byte: u8) -> Self {
+                self.0[0] ^= byte as u64;
+                self
+            }
+
+            fn mul_mod(self, rhs: Self) -> Self {
+                self.wrapping_mul(&rhs)
+            }
+
+            fn to_hex(self) -> String {
+                self.0.iter().rev().map(|limb| format!("{:016x}", limb)).collect()
+            }
+
+            fn finish_u64(self) -> u64 {
+                self.0[0]
+            }
+        }
+    };
+}
+
+// FNV primes 2^168 + 0x163, 2^344 + 0x157 and 2^680 + 0x18d
+impl_fnv_word_for_biguint!(
+    U256,
+    "1000000000000000000000000000000000000000163",
+    "dd268dbcaac550362d98c384c4e576ccc8b1536847b6bbb31023b4c8caee0535"
+);
+impl_fnv_word_for_biguint!(
+    U512,
+    "100000000000000000000000000000000000000000000000000000000000000000000000000000000000157",
+    "b86db0b1171f4416dca1e50f309990acac87d059c90000000000000000000d21\
+     e948f68a34c192f62ea79bc942dbe7ce182036415f56e34bac982aac4afe9fd9"
+);
+impl_fnv_word_for_biguint!(
+    U1024,
+    "10000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000018d",
+    "0000000000000000005f7a76758ecc4d32e56d5a591028b74b29fc4223fdada1\
+     6c3bf34eda3674da9a21d9000000000000000000000000000000000000000000\
+     000000000000000000000000000000000000000000000000000000000004c6d7\
+     eb6e73802734510a555f256cc005ae556bde8cc9c6a93b21aff4b16c71ee90b3"
+);
+
+// FNV-1a: XOR the byte in, then multiply
+pub struct FNV1a<W: FNVWord = u32> {
+    hash_value: W,
+}
+
+impl<W: FNVWord> FNV1a<W> {
+    pub fn new() -> FNV1a<W> {
         FNV1a {
-            prime: 0x01000193,
-            hash_value: 0x811c9dc5,
+            hash_value: W::fnv_offset_basis(),
+        }
+    }
+
+    pub fn hash(&self, input: impl AsRef<[u8]>) -> W {
+        let mut state = FNV1a::new();
+        state.update(input.as_ref());
+        state.finalize()
+    }
+}
+
+impl<W: FNVWord> Default for FNV1a<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Streaming interface: 'hash_value' holds the running state
+impl<W: FNVWord> Hasher for FNV1a<W> {
+    fn write(&mut self, bytes: &[u8]) {
+        let prime = W::fnv_prime();
+        for &byte in bytes {
+            self.hash_value = self.hash_value.xor_byte(byte);
+            self.hash_value = self.hash_value.mul_mod(prime);
         }
     }
 
-    fn hash(&self, input: &str) -> u32 {
-        let mut hash = self.hash_value;
-        for byte in input.bytes() {
-            hash ^= byte as u32;
-            hash = hash.wrapping_mul(self.prime);
+    // 32-bit values are spread over all 64 bits; widths above 64 bits are
+    // truncated to their low 64 bits
+    fn finish(&self) -> u64 {
+        self.hash_value.finish_u64()
+    }
+}
+
+impl<W: FNVWord> crate::hasher::Hasher for FNV1a<W> {
+    type Output = W;
+
+    fn update(&mut self, data: &[u8]) {
+        self.write(data);
+    }
+
+    fn finalize(&mut self) -> W {
+        self.hash_value
+    }
+
+    fn reset(&mut self) {
+        self.hash_value = W::fnv_offset_basis();
+    }
+}
+
+impl_io_write!(<W: FNVWord> FNV1a<W>);
+
+// Builds 64-bit FNV-1a hashers for HashMap and HashSet
+#[derive(Clone, Copy, Default)]
+pub struct FNV1aBuildHasher;
+
+impl BuildHasher for FNV1aBuildHasher {
+    type Hasher = FNV1a<u64>;
+
+    fn build_hasher(&self) -> FNV1a<u64> {
+        FNV1a::new()
+    }
+}
+
+pub type FnvHashMap<K, V> = HashMap<K, V, FNV1aBuildHasher>;
+pub type FnvHashSet<T> = HashSet<T, FNV1aBuildHasher>;
+
+// FNV-1: multiply, then XOR the byte in
+pub struct FNV1<W: FNVWord = u32> {
+    hash_value: W,
+}
+
+impl<W: FNVWord> FNV1<W> {
+    pub fn new() -> FNV1<W> {
+        FNV1 {
+            hash_value: W::fnv_offset_basis(),
         }
-        hash & 0xFFFFFFFFu32
+    }
+
+    pub fn hash(&self, input: impl AsRef<[u8]>) -> W {
+        let mut state = FNV1::new();
+        state.update(input.as_ref());
+        state.finalize()
     }
 }
 
-fn test_fnv1a() {
-    let fnv1a = FNV1a::new();
-    let test_cases = [
-        ("Hello", 0xf55c314b),
-        ("World", 0xdd60ed33),
-        ("Lua!", 0xd41a8e0),
-    ];
+impl<W: FNVWord> Default for FNV1<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: FNVWord> crate::hasher::Hasher for FNV1<W> {
+    type Output = W;
+
+    fn update(&mut self, data: &[u8]) {
+        let prime = W::fnv_prime();
+        for &byte in data {
+            self.hash_value = self.hash_value.mul_mod(prime);
+            self.hash_value = self.hash_value.xor_byte(byte);
+        }
+    }
+
+    fn finalize(&mut self) -> W {
+        self.hash_value
+    }
 
-    for (i, (input, expected)) in test_cases.iter().enumerate() {
-        let hash = fnv1a.hash(input);
-        let passed = hash == *expected;
-        println!("Test case {}: {}", i + 1, if passed { "PASSED" } else { "FAILED" });
+    fn reset(&mut self) {
+        self.hash_value = W::fnv_offset_basis();
     }
 }
 
-fn main() {
-    test_fnv1a();
+impl_io_write!(<W: FNVWord> FNV1<W>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hasher::digest_chunked;
+    use std::io::{self, Cursor};
+
+    #[test]
+    fn default_width_is_32_bits() {
+        let fnv1a: FNV1a = FNV1a::new();
+        let test_cases = [
+            ("Hello", 0xf55c314b),
+            ("World", 0xdd60ed33),
+            ("Lua!", 0xd41a8e0),
+        ];
+
+        for (input, expected) in test_cases {
+            assert_eq!(fnv1a.hash(input), expected, "input {:?}", input);
+        }
+    }
+
+    // Runs (input, FNV-1, FNV-1a) vectors from the FNV test suite at one width
+    fn check_fnv<W: FNVWord>(name: &str, test_cases: &[(&str, &str, &str)]) {
+        let mut fnv1: FNV1<W> = FNV1::new();
+        let mut fnv1a: FNV1a<W> = FNV1a::new();
+
+        for &(input, expected_fnv1, expected_fnv1a) in test_cases {
+            // One-shot, then streamed through the Hasher trait a byte at a time
+            assert_eq!(fnv1.hash(input).to_hex(), expected_fnv1, "{} FNV-1 {:?}", name, input);
+            assert_eq!(fnv1a.hash(input).to_hex(), expected_fnv1a, "{} FNV-1a {:?}", name, input);
+            let streamed = digest_chunked(&mut fnv1, input.as_bytes(), 1);
+            assert_eq!(streamed.to_hex(), expected_fnv1, "{} FNV-1 streamed {:?}", name, input);
+            let streamed = digest_chunked(&mut fnv1a, input.as_bytes(), 1);
+            assert_eq!(streamed.to_hex(), expected_fnv1a, "{} FNV-1a streamed {:?}", name, input);
+        }
+    }
+
+    #[test]
+    fn fnv_32() {
+        check_fnv::<u32>(
+            "FNV-32",
+            &[
+                ("", "811c9dc5", "811c9dc5"),
+                ("a", "050c5d7e", "e40c292c"),
+                ("foobar", "31f0b262", "bf9cf968"),
+                ("chongo was here!\n", "dd002f35", "d49930d5"),
+                ("\0\0\0\0", "4b95f515", "4b95f515"),
+            ],
+        );
+    }
+
+    #[test]
+    fn fnv_64() {
+        check_fnv::<u64>(
+            "FNV-64",
+            &[
+                ("", "cbf29ce484222325", "cbf29ce484222325"),
+                ("a", "af63bd4c8601b7be", "af63dc4c8601ec8c"),
+                ("foobar", "340d8765a4dda9c2", "85944171f73967e8"),
+                ("chongo was here!\n", "e0aca20b624e4235", "46810940eff5f915"),
+                ("\0\0\0\0", "4d25767f9dce13f5", "4d25767f9dce13f5"),
+            ],
+        );
+    }
+
+    #[test]
+    fn fnv_128() {
+        check_fnv::<u128>(
+            "FNV-128",
+            &[
+                ("", "6c62272e07bb014262b821756295c58d", "6c62272e07bb014262b821756295c58d"),
+                ("a", "d228cb69101a8caf78912b704e4a141e", "d228cb696f1a8caf78912b704e4a8964"),
+                ("foobar", "7896bfea9c3c64bf6dc58353d2c293aa", "343e1662793c64bf6f0d3597ba446f18"),
+                ("chongo was here!\n", "40ab469af9cf0fe57236785215beee65", "d09f538fec03781a034e1e32bab19a75"),
+            ],
+        );
+    }
+
+    #[test]
+    fn fnv_256() {
+        check_fnv::<U256>(
+            "FNV-256",
+            &[
+                (
+                    "a",
+                    "63323fb0f35303ec28dc561d0a33bdfa4de6a99b7266494f6183b2716811381e",
+                    "63323fb0f35303ec28dc751d0a33bdfa4de6a99b7266494f6183b2716811637c",
+                ),
+                (
+                    "foobar",
+                    "b055ea2f2cc3908dddb794c02d3889dc32453dad5ae35b753ac86c6c2ac80d72",
+                    "b055ea2f306cadad4f0f81c02d3889dc32453dad5ae35b753ba1a91084af3428",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn fnv_512() {
+        check_fnv::<U512>(
+            "FNV-512",
+            &[
+                (
+                    "a",
+                    "e43a992dc8fc5ad7de493e3d696d6f85d64326ec28000000000000000011986f\
+                     90c2532caf5be7d88291baa894a395225328b196bd6a8a643fe12cd87b282bde",
+                    "e43a992dc8fc5ad7de493e3d696d6f85d64326ec07000000000000000011986f\
+                     90c2532caf5be7d88291baa894a395225328b196bd6a8a643fe12cd87b27ff88",
+                ),
+                (
+                    "foobar",
+                    "b0ec738d9c6fd969d05f0b35f6c0effd20209465290000004bf99f58ee4196af\
+                     b9700e20110830fea5396b76280e47fd022b6e81331ca1a9cf6faf7123c3fc56",
+                    "b0ec738d9c6fd969d05f0b35f6c0ed53adcacccd8e0000004bf99f58ee4196af\
+                     b9700e20110830fea5396b76280e47fd022b6e81331ca1a9ced729c364be7788",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn fnv_1024() {
+        check_fnv::<U1024>(
+            "FNV-1024",
+            &[
+                (
+                    "a",
+                    "000000000000000098d7c19fbce653df221b9f717d3490ff95ca87fdaef30d1b\
+                     823372f85b24a372f50e38000000000000000000000000000000000000000000\
+                     0000000000000000000000000000000000000000000000000000000007685cd8\
+                     1a491dbccc21ad06648d09a5c8cf5a78482054e91470b33dde77252caef665f6",
+                    "000000000000000098d7c19fbce653df221b9f717d3490ff95ca87fdaef30d1b\
+                     823372f85b24a372f50e57000000000000000000000000000000000000000000\
+                     0000000000000000000000000000000000000000000000000000000007685cd8\
+                     1a491dbccc21ad06648d09a5c8cf5a78482054e91470b33dde77252caef695aa",
+                ),
+                (
+                    "foobar",
+                    "00000631175fa7ae643ad08723d312c9fd024adb91f77f6b19587197a22bcdf2\
+                     3727166c3e596993cf5a8d000000000000000000000000000000000000000000\
+                     00000000000000000000000000000000000000000000004270d11ef418ef08b8\
+                     a49e1e825e547eb39937f819222f3b7fc92a0e470790088882a53ca30e08f65c",
+                    "00000631175fa7ae643ad08723d312c9fd024adb91f77f6b19587197a22bcdf2\
+                     3727166c4572d0b985d5ae000000000000000000000000000000000000000000\
+                     00000000000000000000000000000000000000000000004270d11ef418ef08b8\
+                     a49e1e825e547eb39937f819222f3b7fc92a0e4707900888847a554bacec98b0",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn std_hasher_and_collections() {
+        // Streaming writes in pieces must match the one-shot hash
+        let mut hasher: FNV1a<u64> = FNV1a::new();
+        hasher.write(b"foo");
+        hasher.write(b"");
+        hasher.write(b"bar");
+        assert_eq!(hasher.finish(), 0x85944171f73967e8);
+
+        let mut hasher = FNV1aBuildHasher.build_hasher();
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63dc4c8601ec8c);
+
+        // The 32-bit width must still fill the top bits HashMap looks at
+        let mut hasher: FNV1a<u32> = FNV1a::new();
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), (0xe40c292cu64).wrapping_mul(0x9E3779B97F4A7C15));
+        assert_ne!(hasher.finish() >> 32, 0);
+
+        let mut map: FnvHashMap<&str, i32> = FnvHashMap::default();
+        map.insert("one", 1);
+        map.insert("two", 2);
+        map.insert("three", 3);
+        let mut set: FnvHashSet<u64> = FnvHashSet::default();
+        for i in 0..1000 {
+            set.insert(i * 7);
+        }
+        assert_eq!(map.get("two"), Some(&2));
+        assert_eq!(map.len(), 3);
+        assert!(set.contains(&693));
+        assert!(!set.contains(&694));
+    }
+
+    #[test]
+    fn binary_input() {
+        // Every byte value, so not valid UTF-8
+        let data: Vec<u8> = (0..=255).collect();
+        let fnv32: FNV1a = FNV1a::new();
+        let fnv64: FNV1a<u64> = FNV1a::new();
+
+        assert_eq!(fnv32.hash(&data), 0x90a458c5);
+        assert_eq!(fnv64.hash(&data[..]), 0x4242dc5249c33625);
+
+        let mut streaming: FNV1a<u64> = FNV1a::new();
+        assert_eq!(streaming.digest_reader(Cursor::new(&data)).unwrap(), 0x4242dc5249c33625);
+
+        let big: Vec<u8> = (0..200_000u32).map(|i| (i * 7 + i / 256) as u8).collect();
+        let mut fnv1: FNV1<U256> = FNV1::new();
+        io::copy(&mut Cursor::new(&big), &mut fnv1).unwrap();
+        assert!(fnv1.finalize() == FNV1::<U256>::new().hash(&big));
+    }
+
+    // Timing only; run with `cargo test --release -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_fnv1a_hashmap() {
+        crate::hasher::bench_hashmap::<FNV1aBuildHasher>("FNV-1a 64");
+    }
 }
@@ -0,0 +1,152 @@
+// Streaming interface shared by the hash and checksum modules, so calling
+// code can switch algorithms through a generic parameter.
+
+use std::io::{self, Read};
+
+pub trait Hasher {
+    // Checksum or digest type: u32 for CRC-32, Vec<u8> for BLAKE2, ...
+    type Output;
+
+    // Absorbs more input; may be called any number of times
+    fn update(&mut self, data: &[u8]);
+
+    // Result over everything absorbed since construction or the last reset.
+    // Further updates are only meaningful after a reset.
+    fn finalize(&mut self) -> Self::Output;
+
+    // Returns to the freshly constructed state, keeping any key or parameters
+    fn reset(&mut self);
+
+    // One-shot hash of 'data', discarding any earlier input
+    fn digest(&mut self, data: &[u8]) -> Self::Output {
+        self.reset();
+        self.update(data);
+        self.finalize()
+    }
+
+    // Absorbs everything 'reader' yields; returns the number of bytes read
+    fn update_reader<R: Read>(&mut self, mut reader: R) -> io::Result<u64> {
+        let mut buf = [0u8; 64 * 1024];
+        let mut total = 0;
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) => return Ok(total),
+                Ok(n) => {
+                    self.update(&buf[..n]);
+                    total += n as u64;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    // One-shot hash of a file, socket or any other reader
+    fn digest_reader<R: Read>(&mut self, reader: R) -> io::Result<Self::Output> {
+        self.reset();
+        self.update_reader(reader)?;
+        Ok(self.finalize())
+    }
+}
+
+// Implements std::io::Write for a Hasher by forwarding to update(), so
+// io::copy can stream straight into it. Unused when every algorithm feature
+// is switched off.
+#[allow(unused_macros)]
+macro_rules! impl_io_write {
+    (<$param:ident: $bound:path> $hasher:ty) => {
+        impl<$param: $bound> std::io::Write for $hasher {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                $crate::hasher::Hasher::update(self, buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+    };
+    ($hasher:ty) => {
+        impl std::io::Write for $hasher {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                $crate::hasher::Hasher::update(self, buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+    };
+}
+
+// Feeds 'data' in pieces of 'chunk' bytes; the result must match digest()
+pub fn digest_chunked<H: Hasher>(hasher: &mut H, data: &[u8], chunk: usize) -> H::Output {
+    hasher.reset();
+    for piece in data.chunks(chunk.max(1)) {
+        hasher.update(piece);
+    }
+    hasher.finalize()
+}
+
+// Hands 'data' to 'block' in whole N-byte blocks, carrying a partial block
+// over in 'buf' until the next call completes it. Shared by the block-based
+// hashes; unused when they are all switched off.
+#[allow(dead_code)]
+pub(crate) fn feed_blocks<const N: usize>(
+    buf: &mut [u8; N],
+    buf_len: &mut usize,
+    mut data: &[u8],
+    mut block: impl FnMut(&[u8; N]),
+) {
+    if *buf_len > 0 {
+        let take = (N - *buf_len).min(data.len());
+        buf[*buf_len..*buf_len + take].copy_from_slice(&data[..take]);
+        *buf_len += take;
+        data = &data[take..];
+        if *buf_len < N {
+            return;
+        }
+        block(buf);
+        *buf_len = 0;
+    }
+
+    let mut blocks = data.chunks_exact(N);
+    for chunk in &mut blocks {
+        block(chunk.try_into().unwrap());
+    }
+    let rest = blocks.remainder();
+    buf[..rest.len()].copy_from_slice(rest);
+    *buf_len = rest.len();
+}
+
+// Times inserting and looking up a million string keys in a HashMap built
+// with 'S', next to the default SipHash. Shared by the BuildHasher
+// benchmarks; unused when they are all switched off.
+#[cfg(test)]
+#[allow(dead_code)]
+pub(crate) fn bench_hashmap<S: std::hash::BuildHasher + Default>(name: &str) {
+    use std::collections::hash_map::RandomState;
+    use std::collections::HashMap;
+    use std::time::Instant;
+
+    fn insert_and_lookup<S: std::hash::BuildHasher + Default>(keys: &[String]) -> (f64, usize) {
+        let start = Instant::now();
+        let mut map: HashMap<&str, u64, S> = HashMap::default();
+        for (i, key) in keys.iter().enumerate() {
+            map.insert(key, i as u64);
+        }
+        let hits = keys.iter().filter(|key| map.contains_key(key.as_str())).count();
+        (start.elapsed().as_secs_f64(), hits)
+    }
+
+    const N: u64 = 1_000_000;
+    let keys: Vec<String> = (0..N).map(|i| format!("key-{}", i)).collect();
+
+    let (sip_time, sip_hits) = insert_and_lookup::<RandomState>(&keys);
+    let (time, hits) = insert_and_lookup::<S>(&keys);
+
+    println!("{} string keys, insert + lookup:", N);
+    println!("  {:<18} {:.3}s ({} hits)", "SipHash (default):", sip_time, sip_hits);
+    println!("  {:<18} {:.3}s ({} hits)", format!("{}:", name), time, hits);
+}
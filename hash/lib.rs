@@ -0,0 +1,28 @@
+// Hash functions and checksums. Every algorithm is a cargo feature, all on
+// by default; the streaming Hasher trait is always available.
+
+#[macro_use]
+pub mod hasher;
+
+#[cfg(feature = "adler32")]
+pub mod adler32;
+#[cfg(feature = "blake2b")]
+pub mod blake2b;
+#[cfg(feature = "crc32")]
+pub mod crc32;
+#[cfg(feature = "djb2")]
+pub mod djb2;
+#[cfg(feature = "fnv1a")]
+pub mod fnv1a;
+#[cfg(feature = "murmur3")]
+pub mod murmur3;
+#[cfg(feature = "pearson")]
+pub mod pearson;
+#[cfg(feature = "quality")]
+pub mod quality;
+#[cfg(feature = "xxhash")]
+pub mod xxhash;
+#[cfg(feature = "zobrist")]
+pub mod zobrist;
+
+pub use hasher::Hasher;
@@ -0,0 +1,458 @@
+// MurmurHash3 by Austin Appleby, following the SMHasher reference code.
+// x86_32 and x86_128 work on 32-bit lanes, x64_128 on 64-bit lanes; the two
+// 128-bit variants are different functions and give different digests.
+// 128-bit digests are returned as a u128 whose little-endian bytes are the
+// bytes the reference implementation writes out.
+
+use crate::hasher::{feed_blocks, Hasher};
+
+const C1_32: u32 = 0xcc9e2d51;
+const C2_32: u32 = 0x1b873593;
+
+const C1_X86_128: u32 = 0x239b961b;
+const C2_X86_128: u32 = 0xab0e9789;
+const C3_X86_128: u32 = 0x38b34ae5;
+const C4_X86_128: u32 = 0xa1e38b93;
+
+const C1_X64_128: u64 = 0x87c37b91114253d5;
+const C2_X64_128: u64 = 0x4cf5ad432745937f;
+
+fn fmix32(mut h: u32) -> u32 {
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x85ebca6b);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0xc2b2ae35);
+    h ^ (h >> 16)
+}
+
+fn fmix64(mut k: u64) -> u64 {
+    k ^= k >> 33;
+    k = k.wrapping_mul(0xff51afd7ed558ccd);
+    k ^= k >> 33;
+    k = k.wrapping_mul(0xc4ceb9fe1a85ec53);
+    k ^ (k >> 33)
+}
+
+// Multiply, rotate, multiply: how every lane premixes its input word
+fn mix_k32(k: u32, rotation: u32, c_a: u32, c_b: u32) -> u32 {
+    k.wrapping_mul(c_a).rotate_left(rotation).wrapping_mul(c_b)
+}
+
+fn mix_k64(k: u64, rotation: u32, c_a: u64, c_b: u64) -> u64 {
+    k.wrapping_mul(c_a).rotate_left(rotation).wrapping_mul(c_b)
+}
+
+fn read_u32(bytes: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], i: usize) -> u64 {
+    u64::from_le_bytes(bytes[i..i + 8].try_into().unwrap())
+}
+
+// The tail block zero-padded. Mixing a zero word is a no-op, so the padded
+// lanes can go through the same steps as the ones holding tail bytes.
+fn padded_tail<const N: usize>(buf: &[u8; N], buf_len: usize) -> [u8; N] {
+    let mut tail = [0u8; N];
+    tail[..buf_len].copy_from_slice(&buf[..buf_len]);
+    tail
+}
+
+pub struct Murmur3x86_32 {
+    seed: u32,
+    h1: u32,
+    buf: [u8; 4],
+    buf_len: usize,
+    total_len: u64,
+}
+
+impl Murmur3x86_32 {
+    pub fn new() -> Murmur3x86_32 {
+        Murmur3x86_32::with_seed(0)
+    }
+
+    pub fn with_seed(seed: u32) -> Murmur3x86_32 {
+        Murmur3x86_32 {
+            seed,
+            h1: seed,
+            buf: [0; 4],
+            buf_len: 0,
+            total_len: 0,
+        }
+    }
+
+    // One-shot hash with this hasher's seed; the streaming state is untouched
+    pub fn hash(&self, input: impl AsRef<[u8]>) -> u32 {
+        Murmur3x86_32::with_seed(self.seed).digest(input.as_ref())
+    }
+}
+
+impl Default for Murmur3x86_32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for Murmur3x86_32 {
+    type Output = u32;
+
+    fn update(&mut self, data: &[u8]) {
+        self.total_len += data.len() as u64;
+        let h1 = &mut self.h1;
+        feed_blocks(&mut self.buf, &mut self.buf_len, data, |block| {
+            *h1 ^= mix_k32(u32::from_le_bytes(*block), 15, C1_32, C2_32);
+            *h1 = h1.rotate_left(13).wrapping_mul(5).wrapping_add(0xe6546b64);
+        });
+    }
+
+    fn finalize(&mut self) -> u32 {
+        let tail = padded_tail(&self.buf, self.buf_len);
+        let mut h1 = self.h1 ^ mix_k32(u32::from_le_bytes(tail), 15, C1_32, C2_32);
+        // The reference takes the length as a 32-bit int
+        h1 ^= self.total_len as u32;
+        fmix32(h1)
+    }
+
+    fn reset(&mut self) {
+        *self = Murmur3x86_32::with_seed(self.seed);
+    }
+}
+
+impl_io_write!(Murmur3x86_32);
+
+pub struct Murmur3x86_128 {
+    seed: u32,
+    h: [u32; 4],
+    buf: [u8; 16],
+    buf_len: usize,
+    total_len: u64,
+}
+
+impl Murmur3x86_128 {
+    pub fn new() -> Murmur3x86_128 {
+        Murmur3x86_128::with_seed(0)
+    }
+
+    pub fn with_seed(seed: u32) -> Murmur3x86_128 {
+        Murmur3x86_128 {
+            seed,
+            h: [seed; 4],
+            buf: [0; 16],
+            buf_len: 0,
+            total_len: 0,
+        }
+    }
+
+    // One-shot hash with this hasher's seed; the streaming state is untouched
+    pub fn hash(&self, input: impl AsRef<[u8]>) -> u128 {
+        Murmur3x86_128::with_seed(self.seed).digest(input.as_ref())
+    }
+
+    // XORs the four premixed words of the tail block into their lanes;
+    // whole blocks also rotate and add after each lane
+    fn mix_tail(h: &mut [u32; 4], block: &[u8; 16]) {
+        let k = |lane: usize| read_u32(block, 4 * lane);
+        h[0] ^= mix_k32(k(0), 15, C1_X86_128, C2_X86_128);
+        h[1] ^= mix_k32(k(1), 16, C2_X86_128, C3_X86_128);
+        h[2] ^= mix_k32(k(2), 17, C3_X86_128, C4_X86_128);
+        h[3] ^= mix_k32(k(3), 18, C4_X86_128, C1_X86_128);
+    }
+}
+
+impl Default for Murmur3x86_128 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for Murmur3x86_128 {
+    type Output = u128;
+
+    fn update(&mut self, data: &[u8]) {
+        self.total_len += data.len() as u64;
+        let h = &mut self.h;
+        feed_blocks(&mut self.buf, &mut self.buf_len, data, |block| {
+            let k = |lane: usize| read_u32(block, 4 * lane);
+            h[0] ^= mix_k32(k(0), 15, C1_X86_128, C2_X86_128);
+            h[0] = h[0]
+                .rotate_left(19)
+                .wrapping_add(h[1])
+                .wrapping_mul(5)
+                .wrapping_add(0x561ccd1b);
+            h[1] ^= mix_k32(k(1), 16, C2_X86_128, C3_X86_128);
+            h[1] = h[1]
+                .rotate_left(17)
+                .wrapping_add(h[2])
+                .wrapping_mul(5)
+                .wrapping_add(0x0bcaa747);
+            h[2] ^= mix_k32(k(2), 17, C3_X86_128, C4_X86_128);
+            h[2] = h[2]
+                .rotate_left(15)
+                .wrapping_add(h[3])
+                .wrapping_mul(5)
+                .wrapping_add(0x96cd1c35);
+            h[3] ^= mix_k32(k(3), 18, C4_X86_128, C1_X86_128);
+            h[3] = h[3]
+                .rotate_left(13)
+                .wrapping_add(h[0])
+                .wrapping_mul(5)
+                .wrapping_add(0x32ac3b17);
+        });
+    }
+
+    fn finalize(&mut self) -> u128 {
+        let mut h = self.h;
+        Murmur3x86_128::mix_tail(&mut h, &padded_tail(&self.buf, self.buf_len));
+
+        for lane in h.iter_mut() {
+            *lane ^= self.total_len as u32;
+        }
+        h[0] = h[0]
+            .wrapping_add(h[1])
+            .wrapping_add(h[2])
+            .wrapping_add(h[3]);
+        for i in 1..4 {
+            h[i] = h[i].wrapping_add(h[0]);
+        }
+        for lane in h.iter_mut() {
+            *lane = fmix32(*lane);
+        }
+        h[0] = h[0]
+            .wrapping_add(h[1])
+            .wrapping_add(h[2])
+            .wrapping_add(h[3]);
+        for i in 1..4 {
+            h[i] = h[i].wrapping_add(h[0]);
+        }
+
+        h.iter()
+            .rev()
+            .fold(0, |acc, &lane| acc << 32 | lane as u128)
+    }
+
+    fn reset(&mut self) {
+        *self = Murmur3x86_128::with_seed(self.seed);
+    }
+}
+
+impl_io_write!(Murmur3x86_128);
+
+pub struct Murmur3x64_128 {
+    seed: u32,
+    h1: u64,
+    h2: u64,
+    buf: [u8; 16],
+    buf_len: usize,
+    total_len: u64,
+}
+
+impl Murmur3x64_128 {
+    pub fn new() -> Murmur3x64_128 {
+        Murmur3x64_128::with_seed(0)
+    }
+
+    pub fn with_seed(seed: u32) -> Murmur3x64_128 {
+        Murmur3x64_128 {
+            seed,
+            h1: seed as u64,
+            h2: seed as u64,
+            buf: [0; 16],
+            buf_len: 0,
+            total_len: 0,
+        }
+    }
+
+    // One-shot hash with this hasher's seed; the streaming state is untouched
+    pub fn hash(&self, input: impl AsRef<[u8]>) -> u128 {
+        Murmur3x64_128::with_seed(self.seed).digest(input.as_ref())
+    }
+}
+
+impl Default for Murmur3x64_128 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for Murmur3x64_128 {
+    type Output = u128;
+
+    fn update(&mut self, data: &[u8]) {
+        self.total_len += data.len() as u64;
+        let (h1, h2) = (&mut self.h1, &mut self.h2);
+        feed_blocks(&mut self.buf, &mut self.buf_len, data, |block| {
+            *h1 ^= mix_k64(read_u64(block, 0), 31, C1_X64_128, C2_X64_128);
+            *h1 = h1
+                .rotate_left(27)
+                .wrapping_add(*h2)
+                .wrapping_mul(5)
+                .wrapping_add(0x52dce729);
+            *h2 ^= mix_k64(read_u64(block, 8), 33, C2_X64_128, C1_X64_128);
+            *h2 = h2
+                .rotate_left(31)
+                .wrapping_add(*h1)
+                .wrapping_mul(5)
+                .wrapping_add(0x38495ab5);
+        });
+    }
+
+    fn finalize(&mut self) -> u128 {
+        let tail = padded_tail(&self.buf, self.buf_len);
+        let mut h1 = self.h1 ^ mix_k64(read_u64(&tail, 0), 31, C1_X64_128, C2_X64_128);
+        let mut h2 = self.h2 ^ mix_k64(read_u64(&tail, 8), 33, C2_X64_128, C1_X64_128);
+
+        h1 ^= self.total_len;
+        h2 ^= self.total_len;
+        h1 = h1.wrapping_add(h2);
+        h2 = h2.wrapping_add(h1);
+        h1 = fmix64(h1);
+        h2 = fmix64(h2);
+        h1 = h1.wrapping_add(h2);
+        h2 = h2.wrapping_add(h1);
+
+        (h2 as u128) << 64 | h1 as u128
+    }
+
+    fn reset(&mut self) {
+        *self = Murmur3x64_128::with_seed(self.seed);
+    }
+}
+
+impl_io_write!(Murmur3x64_128);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hasher::digest_chunked;
+    use std::io::{self, Cursor};
+
+    // (input, seed, x86_32) from the published MurmurHash3 test vectors
+    const X86_32_KNOWN_ANSWERS: [(&str, u32, u32); 7] = [
+        ("", 0, 0x00000000),
+        ("", 1, 0x514E28B7),
+        ("", 0xFFFFFFFF, 0x81F16F39),
+        ("\0\0\0\0", 0, 0x2362F9DE),
+        ("aaaa", 0x9747B28C, 0x5A97808A),
+        ("Hello, world!", 1234, 0xFAF6CDB3),
+        ("The quick brown fox jumps over the lazy dog", 0, 0x2E4FF723),
+    ];
+
+    // SMHasher's verification code: hash keys {}, {0}, {0, 1}, ... {0..254}
+    // with seeds 256 down to 1, hash the concatenated digests with seed 0,
+    // and read the first four bytes of that as a little-endian u32
+    fn verification_code(hash: impl Fn(&[u8], u32) -> Vec<u8>) -> u32 {
+        let mut key = [0u8; 256];
+        let mut digests = vec![];
+        for i in 0..256 {
+            key[i] = i as u8;
+            digests.extend(hash(&key[..i], 256 - i as u32));
+        }
+        u32::from_le_bytes(hash(&digests, 0)[..4].try_into().unwrap())
+    }
+
+    #[test]
+    fn x86_32_known_answers() {
+        for (input, seed, expected) in X86_32_KNOWN_ANSWERS {
+            assert_eq!(
+                Murmur3x86_32::with_seed(seed).hash(input),
+                expected,
+                "{:?} seed {:#x}",
+                input,
+                seed
+            );
+        }
+    }
+
+    #[test]
+    fn x64_128_known_answers() {
+        // Canonical digest 6c1b07bc7bbc4be347939ac4a93c437a, read little-endian
+        let fox = "The quick brown fox jumps over the lazy dog";
+        assert_eq!(
+            Murmur3x64_128::new().hash(fox),
+            0x7a433ca9c49a9347_e34bbc7bbc071b6c
+        );
+        assert_eq!(Murmur3x64_128::new().hash(""), 0);
+        assert_eq!(Murmur3x86_128::new().hash(""), 0);
+    }
+
+    #[test]
+    fn smhasher_verification() {
+        let x86_32 = verification_code(|key, seed| {
+            Murmur3x86_32::with_seed(seed)
+                .hash(key)
+                .to_le_bytes()
+                .to_vec()
+        });
+        let x86_128 = verification_code(|key, seed| {
+            Murmur3x86_128::with_seed(seed)
+                .hash(key)
+                .to_le_bytes()
+                .to_vec()
+        });
+        let x64_128 = verification_code(|key, seed| {
+            Murmur3x64_128::with_seed(seed)
+                .hash(key)
+                .to_le_bytes()
+                .to_vec()
+        });
+        assert_eq!(x86_32, 0xB0F57EE3);
+        assert_eq!(x86_128, 0xB3ECE62A);
+        assert_eq!(x64_128, 0x6384BA69);
+    }
+
+    #[test]
+    fn streaming_matches_one_shot() {
+        let data: Vec<u8> = (0..100u32).map(|i| (i * 31 + 7) as u8).collect();
+        for len in 0..=data.len() {
+            let input = &data[..len];
+            let x86_32 = Murmur3x86_32::with_seed(42).hash(input);
+            let x86_128 = Murmur3x86_128::with_seed(42).hash(input);
+            let x64_128 = Murmur3x64_128::with_seed(42).hash(input);
+            for chunk in 1..=17 {
+                assert_eq!(
+                    digest_chunked(&mut Murmur3x86_32::with_seed(42), input, chunk),
+                    x86_32,
+                    "len {}",
+                    len
+                );
+                assert_eq!(
+                    digest_chunked(&mut Murmur3x86_128::with_seed(42), input, chunk),
+                    x86_128,
+                    "len {}",
+                    len
+                );
+                assert_eq!(
+                    digest_chunked(&mut Murmur3x64_128::with_seed(42), input, chunk),
+                    x64_128,
+                    "len {}",
+                    len
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn hasher_reset_keeps_seed() {
+        let mut murmur = Murmur3x86_32::with_seed(1234);
+        murmur.update(b"stale");
+        assert_eq!(murmur.digest(b"Hello, world!"), 0xFAF6CDB3);
+
+        let mut murmur = Murmur3x64_128::with_seed(7);
+        let fresh = murmur.hash(b"abc");
+        murmur.update(b"stale");
+        murmur.reset();
+        murmur.update(b"abc");
+        assert_eq!(murmur.finalize(), fresh);
+    }
+
+    #[test]
+    fn reader_and_io_copy() {
+        let text = "The quick brown fox jumps over the lazy dog";
+        let mut murmur = Murmur3x86_32::new();
+        assert_eq!(murmur.digest_reader(Cursor::new(text)).unwrap(), 0x2E4FF723);
+
+        let mut copied = Murmur3x64_128::new();
+        io::copy(&mut Cursor::new(text), &mut copied).unwrap();
+        assert_eq!(copied.finalize(), 0x7a433ca9c49a9347_e34bbc7bbc071b6c);
+    }
+}
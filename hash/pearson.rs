@@ -0,0 +1,399 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom; // Import for shuffling
+
+use crate::hasher::Hasher;
+
+// Permutation table published in Pearson's 1990 CACM paper
+pub const PEARSON_1990_TABLE: [u8; 256] = [
+    1, 87, 49, 12, 176, 178, 102, 166, 121, 193, 6, 84, 249, 230, 44, 163,
+    14, 197, 213, 181, 161, 85, 218, 80, 64, 239, 24, 226, 236, 142, 38, 200,
+    110, 177, 104, 103, 141, 253, 255, 50, 77, 101, 81, 18, 45, 96, 31, 222,
+    25, 107, 190, 70, 86, 237, 240, 34, 72, 242, 20, 214, 244, 227, 149, 235,
+    97, 234, 57, 22, 60, 250, 82, 175, 208, 5, 127, 199, 111, 62, 135, 248,
+    174, 169, 211, 58, 66, 154, 106, 195, 245, 171, 17, 187, 182, 179, 0, 243,
+    132, 56, 148, 75, 128, 133, 158, 100, 130, 126, 91, 13, 153, 246, 216, 219,
+    119, 68, 223, 78, 83, 88, 201, 99, 122, 11, 92, 32, 136, 114, 52, 10,
+    138, 30, 48, 183, 156, 35, 61, 26, 143, 74, 251, 94, 129, 162, 63, 152,
+    170, 7, 115, 167, 241, 206, 3, 150, 55, 59, 151, 220, 90, 53, 23, 131,
+    125, 173, 15, 238, 79, 95, 89, 16, 105, 137, 225, 224, 217, 160, 37, 123,
+    118, 73, 2, 157, 46, 116, 9, 145, 134, 228, 207, 212, 202, 215, 69, 229,
+    27, 188, 67, 124, 168, 252, 42, 4, 29, 108, 21, 247, 19, 205, 39, 203,
+    233, 40, 186, 147, 198, 192, 155, 33, 164, 191, 98, 204, 165, 180, 117, 76,
+    140, 36, 210, 172, 41, 54, 159, 8, 185, 232, 113, 196, 231, 47, 146, 120,
+    51, 65, 28, 144, 254, 221, 93, 189, 194, 139, 112, 43, 71, 109, 184, 209,
+];
+
+pub struct Pearson {
+    t: [u8; 256],
+    // Running 8-bit hash for the streaming interface
+    h: u8,
+}
+
+impl Pearson {
+    pub fn new() -> Pearson {
+        // Shuffle T using a fixed random seed for reproducibility
+        let seed: [u8; 32] = [0; 32];
+        // Initialize the random number generator with the fixed seed
+        let rng: StdRng = SeedableRng::from_seed(seed);
+
+        Pearson::shuffled(rng)
+    }
+
+    // Permutation drawn from a generator seeded with 'seed'
+    pub fn with_seed(seed: u64) -> Pearson {
+        Pearson::shuffled(StdRng::seed_from_u64(seed))
+    }
+
+    // Permutation derived from an arbitrary-length key with the RC4 key
+    // schedule, so it does not depend on the rand crate's generator
+    pub fn with_key(key: &[u8]) -> Result<Pearson, &'static str> {
+        if key.is_empty() {
+            return Err("Key must not be empty");
+        }
+
+        let mut t = Pearson::identity();
+        let mut j = 0u8;
+        for i in 0..256 {
+            j = j.wrapping_add(t[i]).wrapping_add(key[i % key.len()]);
+            t.swap(i, j as usize);
+        }
+
+        Ok(Pearson { t, h: 0 })
+    }
+
+    // Accepts a caller-supplied table if it is a permutation of 0..=255
+    pub fn from_table(table: &[u8]) -> Result<Pearson, &'static str> {
+        if table.len() != 256 {
+            return Err("Table must have 256 entries");
+        }
+
+        let mut seen = [false; 256];
+        for &entry in table {
+            if seen[entry as usize] {
+                return Err("Table is not a permutation");
+            }
+            seen[entry as usize] = true;
+        }
+
+        let mut t = [0u8; 256];
+        t.copy_from_slice(table);
+        Ok(Pearson { t, h: 0 })
+    }
+
+    // Searches seeded permutations for one that maps every keyword to a
+    // distinct 8-bit hash
+    pub fn perfect_for(keywords: &[&str], max_attempts: u64) -> Result<Pearson, &'static str> {
+        if keywords.len() > 256 {
+            return Err("More than 256 keywords cannot hash perfectly to 8 bits");
+        }
+        for (i, keyword) in keywords.iter().enumerate() {
+            if keywords[..i].contains(keyword) {
+                return Err("Duplicate keyword");
+            }
+        }
+
+        for seed in 0..max_attempts {
+            let pearson = Pearson::with_seed(seed);
+            let mut seen = [false; 256];
+            let perfect = keywords.iter().all(|keyword| {
+                let h = pearson.hash(keyword) as usize;
+                !std::mem::replace(&mut seen[h], true)
+            });
+            if perfect {
+                return Ok(pearson);
+            }
+        }
+
+        Err("No perfect table found")
+    }
+
+    fn identity() -> [u8; 256] {
+        let mut t = [0u8; 256];
+        for (i, entry) in t.iter_mut().enumerate() {
+            *entry = i as u8;
+        }
+        t
+    }
+
+    fn shuffled(mut rng: StdRng) -> Pearson {
+        let mut t = Pearson::identity();
+
+        // Shuffle the array using Fisher-Yates algorithm
+        t.shuffle(&mut rng);
+
+        Pearson { t, h: 0 }
+    }
+
+    pub fn hash(&self, input: impl AsRef<[u8]>) -> u8 {
+        let mut h = 0u8;
+        for &byte in input.as_ref() {
+            h = self.t[(h ^ byte) as usize];
+        }
+        h
+    }
+
+    // Wider hashes run the table once per output byte; see PearsonWide
+    pub fn hash16(&self, input: impl AsRef<[u8]>) -> u16 {
+        let mut state = Pearson16::new(self);
+        state.update(input.as_ref());
+        state.finalize()
+    }
+
+    pub fn hash32(&self, input: impl AsRef<[u8]>) -> u32 {
+        let mut state = Pearson32::new(self);
+        state.update(input.as_ref());
+        state.finalize()
+    }
+
+    pub fn hash64(&self, input: impl AsRef<[u8]>) -> u64 {
+        let mut state = Pearson64::new(self);
+        state.update(input.as_ref());
+        state.finalize()
+    }
+}
+
+impl Default for Pearson {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for Pearson {
+    type Output = u8;
+
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.h = self.t[(self.h ^ byte) as usize];
+        }
+    }
+
+    fn finalize(&mut self) -> u8 {
+        self.h
+    }
+
+    fn reset(&mut self) {
+        self.h = 0;
+    }
+}
+
+impl_io_write!(Pearson);
+
+// Streaming form of hash16, hash32 and hash64: one running 8-bit hash per
+// output byte, each seeded from the first input byte plus its index
+pub struct PearsonWide<const BYTES: usize> {
+    t: [u8; 256],
+    lanes: [u8; BYTES],
+    started: bool,
+}
+
+impl<const BYTES: usize> PearsonWide<BYTES> {
+    fn absorb(&mut self, mut data: &[u8]) {
+        if !self.started {
+            let Some((&first, rest)) = data.split_first() else {
+                return;
+            };
+            for (j, lane) in self.lanes.iter_mut().enumerate() {
+                *lane = self.t[first.wrapping_add(j as u8) as usize];
+            }
+            self.started = true;
+            data = rest;
+        }
+
+        for &byte in data {
+            for lane in self.lanes.iter_mut() {
+                *lane = self.t[(*lane ^ byte) as usize];
+            }
+        }
+    }
+}
+
+// Only the widths with a one-shot counterpart get a constructor and Hasher
+macro_rules! impl_pearson_wide {
+    ($bytes:literal, $output:ty) => {
+        impl PearsonWide<$bytes> {
+            // Uses the same permutation as 'pearson'
+            pub fn new(pearson: &Pearson) -> PearsonWide<$bytes> {
+                PearsonWide {
+                    t: pearson.t,
+                    lanes: [0; $bytes],
+                    started: false,
+                }
+            }
+        }
+
+        impl Hasher for PearsonWide<$bytes> {
+            type Output = $output;
+
+            fn update(&mut self, data: &[u8]) {
+                self.absorb(data);
+            }
+
+            fn finalize(&mut self) -> $output {
+                self.lanes.iter().fold(0, |hash, &lane| (hash << 8) | lane as $output)
+            }
+
+            fn reset(&mut self) {
+                self.lanes = [0; $bytes];
+                self.started = false;
+            }
+        }
+
+        impl_io_write!(PearsonWide<$bytes>);
+    };
+}
+
+impl_pearson_wide!(2, u16);
+impl_pearson_wide!(4, u32);
+impl_pearson_wide!(8, u64);
+
+pub type Pearson16 = PearsonWide<2>;
+pub type Pearson32 = PearsonWide<4>;
+pub type Pearson64 = PearsonWide<8>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hasher::digest_chunked;
+    use std::io::{self, Cursor};
+
+    // (input, 8-bit, 16-bit, 32-bit, 64-bit) over the table from Pearson's
+    // paper, checked against an independent implementation
+    const PAPER_KNOWN_ANSWERS: [(&str, u8, u16, u32, u64); 4] = [
+        ("a", 56, 0x3894, 0x38944b80, 0x38944b80859e6482),
+        ("abc", 223, 0xdf95, 0xdf956649, 0xdf9566490d8db278),
+        ("Hello", 57, 0x397b, 0x397b9cb3, 0x397b9cb33ea9060d),
+        ("The quick brown fox jumps over the lazy dog", 165, 0xa526, 0xa526e0da, 0xa526e0dabc6d4423),
+    ];
+
+    #[test]
+    fn paper_table_known_answers() {
+        let paper = Pearson::from_table(&PEARSON_1990_TABLE).unwrap();
+        for (input, expected8, expected16, expected32, expected64) in PAPER_KNOWN_ANSWERS {
+            assert_eq!(paper.hash(input), expected8, "input {:?}", input);
+            assert_eq!(paper.hash16(input), expected16, "input {:?}", input);
+            assert_eq!(paper.hash32(input), expected32, "input {:?}", input);
+            assert_eq!(paper.hash64(input), expected64, "input {:?}", input);
+        }
+        assert_eq!(paper.hash(""), 0);
+        assert_eq!(paper.hash64(""), 0);
+    }
+
+    // Regression values for the table shuffled from the fixed default seed;
+    // they change if the rand crate changes its StdRng algorithm
+    #[test]
+    fn default_table_known_answers() {
+        let pearson = Pearson::new();
+        let test_cases = [
+            ("Hello", 0xf809, 0xf80910f3, 0xf80910f33c9e02d8),
+            ("World", 0x9e4c, 0x9e4cea03, 0x9e4cea031110dd6a),
+            ("Rust!", 0x7c92, 0x7c927867, 0x7c9278673cd994b0),
+            ("a", 0x0440, 0x04406003, 0x044060033259d3ac),
+        ];
+
+        for (input, expected16, expected32, expected64) in test_cases {
+            assert_eq!(pearson.hash16(input), expected16, "input {:?}", input);
+            assert_eq!(pearson.hash32(input), expected32, "input {:?}", input);
+            assert_eq!(pearson.hash64(input), expected64, "input {:?}", input);
+            // The first output byte is always the plain 8-bit Pearson hash
+            assert_eq!((expected64 >> 56) as u8, pearson.hash(input), "input {:?}", input);
+        }
+    }
+
+    #[test]
+    fn streaming_matches_one_shot() {
+        let mut pearson = Pearson::from_table(&PEARSON_1990_TABLE).unwrap();
+        for (input, expected, _, _, _) in PAPER_KNOWN_ANSWERS {
+            assert_eq!(digest_chunked(&mut pearson, input.as_bytes(), 2), expected, "input {:?}", input);
+            assert_eq!(pearson.digest(input.as_bytes()), expected, "input {:?}", input);
+        }
+    }
+
+    #[test]
+    fn wide_streaming_matches_one_shot() {
+        let paper = Pearson::from_table(&PEARSON_1990_TABLE).unwrap();
+        let mut pearson16 = Pearson16::new(&paper);
+        let mut pearson32 = Pearson32::new(&paper);
+        let mut pearson64 = Pearson64::new(&paper);
+        for (input, _, expected16, expected32, expected64) in PAPER_KNOWN_ANSWERS {
+            for chunk_size in [1, 2, 7] {
+                let bytes = input.as_bytes();
+                assert_eq!(digest_chunked(&mut pearson16, bytes, chunk_size), expected16, "input {:?}", input);
+                assert_eq!(digest_chunked(&mut pearson32, bytes, chunk_size), expected32, "input {:?}", input);
+                assert_eq!(digest_chunked(&mut pearson64, bytes, chunk_size), expected64, "input {:?}", input);
+            }
+        }
+
+        // Empty updates before the first byte leave the state untouched
+        pearson64.reset();
+        pearson64.update(b"");
+        assert_eq!(pearson64.finalize(), 0);
+        pearson64.update(b"a");
+        assert_eq!(pearson64.finalize(), paper.hash64("a"));
+
+        let big: Vec<u8> = (0..200_000u32).map(|i| (i * 7 + i / 256) as u8).collect();
+        pearson32.reset();
+        io::copy(&mut Cursor::new(&big), &mut pearson32).unwrap();
+        assert_eq!(pearson32.finalize(), paper.hash32(&big));
+    }
+
+    #[test]
+    fn keyed_and_seeded_tables_are_deterministic() {
+        let default = Pearson::new();
+
+        let keyed = Pearson::with_key(b"secret").unwrap();
+        assert_eq!(keyed.t, Pearson::with_key(b"secret").unwrap().t);
+        assert_ne!(keyed.t, Pearson::with_key(b"secreu").unwrap().t);
+        assert_ne!(keyed.t, default.t);
+
+        let seeded = Pearson::with_seed(42);
+        assert_eq!(seeded.t, Pearson::with_seed(42).t);
+        assert_ne!(seeded.t, Pearson::with_seed(43).t);
+
+        // Derived tables are permutations
+        assert!(Pearson::from_table(&keyed.t).is_ok());
+        assert!(Pearson::from_table(&seeded.t).is_ok());
+    }
+
+    #[test]
+    fn invalid_tables_rejected() {
+        let mut duplicate = PEARSON_1990_TABLE;
+        duplicate[0] = duplicate[1];
+        assert_eq!(Pearson::from_table(&duplicate).err(), Some("Table is not a permutation"));
+        assert_eq!(Pearson::from_table(&PEARSON_1990_TABLE[..255]).err(), Some("Table must have 256 entries"));
+        assert_eq!(Pearson::with_key(b"").err(), Some("Key must not be empty"));
+    }
+
+    #[test]
+    fn perfect_hash_over_c_keywords() {
+        let keywords = [
+            "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else",
+            "enum", "extern", "float", "for", "goto", "if", "int", "long", "register", "return",
+            "short", "signed", "sizeof", "static", "struct", "switch", "typedef", "union", "unsigned",
+            "void", "volatile", "while",
+        ];
+        let pearson = Pearson::perfect_for(&keywords, 10_000).unwrap();
+        let mut hashes: Vec<u8> = keywords.iter().map(|keyword| pearson.hash(keyword)).collect();
+        hashes.sort_unstable();
+        hashes.dedup();
+        assert_eq!(hashes.len(), keywords.len());
+
+        assert!(Pearson::perfect_for(&["if", "if"], 10).is_err());
+    }
+
+    #[test]
+    fn binary_input() {
+        let mut paper = Pearson::from_table(&PEARSON_1990_TABLE).unwrap();
+        // Every byte value, so not valid UTF-8
+        let data: Vec<u8> = (0..=255).collect();
+
+        assert_eq!(paper.hash(&data), 44);
+        assert_eq!(paper.hash16(&data[..]), 0x2c64);
+        assert_eq!(paper.digest_reader(Cursor::new(&data)).unwrap(), 44);
+
+        let big: Vec<u8> = (0..200_000u32).map(|i| (i * 7 + i / 256) as u8).collect();
+        paper.reset();
+        io::copy(&mut Cursor::new(&big), &mut paper).unwrap();
+        assert_eq!(paper.finalize(), paper.hash(&big));
+    }
+}
@@ -0,0 +1,626 @@
+// SMHasher-style quality analysis that runs against any Hasher: avalanche
+// matrix and strict avalanche criterion, bit independence, bucket
+// distribution and collision counts over sparse, cyclic and text keysets.
+// Everything is seeded, so two runs over the same hasher agree exactly.
+
+use crate::hasher::Hasher;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+use std::fmt;
+
+// Hash outputs the analysis can take apart bit by bit. Bit i of an output
+// is bit i % 8 of byte i / 8 of its little-endian encoding.
+pub trait HashOutput: Eq + std::hash::Hash {
+    fn output_bytes(&self) -> Vec<u8>;
+}
+
+macro_rules! impl_hash_output {
+    ($($word:ty),*) => {
+        $(
+            impl HashOutput for $word {
+                fn output_bytes(&self) -> Vec<u8> {
+                    self.to_le_bytes().to_vec()
+                }
+            }
+        )*
+    };
+}
+
+impl_hash_output!(u8, u16, u32, u64, u128);
+
+impl HashOutput for Vec<u8> {
+    fn output_bytes(&self) -> Vec<u8> {
+        self.clone()
+    }
+}
+
+fn bit(bytes: &[u8], i: usize) -> bool {
+    bytes[i / 8] >> (i % 8) & 1 == 1
+}
+
+// Low 64 bits of an output, used to pick a bucket
+fn low_u64(bytes: &[u8]) -> u64 {
+    bytes.iter().take(8).rev().fold(0, |acc, &b| acc << 8 | b as u64)
+}
+
+fn output_bits<H: Hasher>(hasher: &mut H) -> usize
+where
+    H::Output: HashOutput,
+{
+    hasher.digest(&[]).output_bytes().len() * 8
+}
+
+fn random_key(rng: &mut StdRng, len: usize) -> Vec<u8> {
+    (0..len).map(|_| rng.gen()).collect()
+}
+
+pub struct Avalanche {
+    pub input_bits: usize,
+    pub output_bits: usize,
+    pub samples: usize,
+    // flips[i][j]: how many samples had output bit j change when input bit i
+    // was flipped
+    pub flips: Vec<Vec<u64>>,
+}
+
+impl Avalanche {
+    // Probability that flipping input bit i flips output bit j; the strict
+    // avalanche criterion wants 0.5 everywhere
+    pub fn probability(&self, i: usize, j: usize) -> f64 {
+        self.flips[i][j] as f64 / self.samples as f64
+    }
+
+    // |2p - 1| for one cell: 0 is ideal, 1 means the output bit always or
+    // never changes with that input bit
+    pub fn bias(&self, i: usize, j: usize) -> f64 {
+        (2.0 * self.probability(i, j) - 1.0).abs()
+    }
+
+    // Largest bias over the matrix and the input and output bit it belongs to
+    pub fn worst_bias(&self) -> (f64, usize, usize) {
+        let mut worst = (0.0, 0, 0);
+        for i in 0..self.input_bits {
+            for j in 0..self.output_bits {
+                let bias = self.bias(i, j);
+                if bias > worst.0 {
+                    worst = (bias, i, j);
+                }
+            }
+        }
+        worst
+    }
+
+    pub fn mean_bias(&self) -> f64 {
+        let cells = (self.input_bits * self.output_bits) as f64;
+        let total: f64 = (0..self.input_bits)
+            .flat_map(|i| (0..self.output_bits).map(move |j| (i, j)))
+            .map(|(i, j)| self.bias(i, j))
+            .sum();
+        total / cells
+    }
+}
+
+// The matrix as one row per input bit and one character per output bit:
+// '.' for a bias under 0.1, otherwise the bias in tenths, 0 to 9 (9 also
+// covering 1.0). Only the first 64 output bits are drawn.
+impl fmt::Display for Avalanche {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for i in 0..self.input_bits {
+            let row: String = (0..self.output_bits.min(64))
+                .map(|j| match (self.bias(i, j) * 10.0) as u32 {
+                    0 => '.',
+                    tenths => char::from_digit(tenths.min(9), 10).unwrap(),
+                })
+                .collect();
+            writeln!(f, "{:>4} {}", i, row)?;
+        }
+        Ok(())
+    }
+}
+
+// Flips every bit of 'samples' random keys of 'key_len' bytes and records
+// which output bits change
+pub fn avalanche<H: Hasher>(hasher: &mut H, key_len: usize, samples: usize, seed: u64) -> Avalanche
+where
+    H::Output: HashOutput,
+{
+    let input_bits = key_len * 8;
+    let output_bits = output_bits(hasher);
+    let mut flips = vec![vec![0u64; output_bits]; input_bits];
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for _ in 0..samples {
+        let mut key = random_key(&mut rng, key_len);
+        let base = hasher.digest(&key).output_bytes();
+        for (i, row) in flips.iter_mut().enumerate() {
+            key[i / 8] ^= 1 << (i % 8);
+            let flipped = hasher.digest(&key).output_bytes();
+            key[i / 8] ^= 1 << (i % 8);
+
+            for (j, count) in row.iter_mut().enumerate() {
+                if bit(&base, j) != bit(&flipped, j) {
+                    *count += 1;
+                }
+            }
+        }
+    }
+
+    Avalanche { input_bits, output_bits, samples, flips }
+}
+
+pub struct BitIndependence {
+    // Output bits examined: the first 64 at most
+    pub output_bits: usize,
+    // Single-bit flips tried, samples times input bits
+    pub trials: u64,
+    // |4 * P(both change) - 1| for the worst pair of output bits, and that
+    // pair. Independent bits that each change half the time score 0.
+    pub worst_bias: f64,
+    pub worst_pair: (usize, usize),
+}
+
+// Bit independence criterion: when an input bit flips, whether one output
+// bit changes should say nothing about whether another does
+pub fn bit_independence<H: Hasher>(hasher: &mut H, key_len: usize, samples: usize, seed: u64) -> BitIndependence
+where
+    H::Output: HashOutput,
+{
+    let bits = output_bits(hasher).min(64);
+    let mut both = vec![vec![0u64; bits]; bits];
+    let mut trials = 0;
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for _ in 0..samples {
+        let mut key = random_key(&mut rng, key_len);
+        let base = low_u64(&hasher.digest(&key).output_bytes());
+        for i in 0..key_len * 8 {
+            key[i / 8] ^= 1 << (i % 8);
+            let changed = base ^ low_u64(&hasher.digest(&key).output_bytes());
+            key[i / 8] ^= 1 << (i % 8);
+            trials += 1;
+
+            for j in (0..bits).filter(|&j| changed >> j & 1 == 1) {
+                for k in (j + 1..bits).filter(|&k| changed >> k & 1 == 1) {
+                    both[j][k] += 1;
+                }
+            }
+        }
+    }
+
+    let mut worst_bias = 0.0;
+    let mut worst_pair = (0, 0);
+    for (j, row) in both.iter().enumerate() {
+        for (k, &count) in row.iter().enumerate().skip(j + 1) {
+            let bias = (4.0 * count as f64 / trials as f64 - 1.0).abs();
+            if bias > worst_bias {
+                worst_bias = bias;
+                worst_pair = (j, k);
+            }
+        }
+    }
+
+    BitIndependence { output_bits: bits, trials, worst_bias, worst_pair }
+}
+
+pub struct Distribution {
+    pub keys: usize,
+    // Requested bucket count, capped at the number of distinct outputs
+    pub buckets: usize,
+    pub chi_square: f64,
+}
+
+impl Distribution {
+    pub fn degrees_of_freedom(&self) -> usize {
+        self.buckets - 1
+    }
+
+    // Standard deviations the chi-square lies above (or below) what a
+    // uniform hash would give; within about +-3 is unremarkable
+    pub fn z_score(&self) -> f64 {
+        let df = self.degrees_of_freedom() as f64;
+        (self.chi_square - df) / (2.0 * df).sqrt()
+    }
+}
+
+// Chi-square of the keys spread over 'buckets' buckets by the low bits of
+// their hashes
+pub fn distribution<H: Hasher>(hasher: &mut H, keys: &[Vec<u8>], buckets: usize) -> Distribution
+where
+    H::Output: HashOutput,
+{
+    let bits = output_bits(hasher);
+    let buckets = if bits < usize::BITS as usize { buckets.min(1 << bits) } else { buckets }.max(2);
+    let mut counts = vec![0u64; buckets];
+    for key in keys {
+        let h = low_u64(&hasher.digest(key).output_bytes());
+        counts[(h % buckets as u64) as usize] += 1;
+    }
+
+    let expected = keys.len() as f64 / buckets as f64;
+    let chi_square = counts.iter().map(|&c| (c as f64 - expected).powi(2) / expected).sum();
+
+    Distribution { keys: keys.len(), buckets, chi_square }
+}
+
+pub struct Collisions {
+    pub keys: usize,
+    // Keys whose full hash output matched an earlier key's
+    pub collisions: usize,
+    // What a random function with the same output size would average
+    pub expected: f64,
+}
+
+pub fn collisions<H: Hasher>(hasher: &mut H, keys: &[Vec<u8>]) -> Collisions
+where
+    H::Output: HashOutput,
+{
+    let bits = output_bits(hasher);
+    let mut seen = HashSet::with_capacity(keys.len());
+    let collisions = keys.iter().filter(|key| !seen.insert(hasher.digest(key))).count();
+
+    let n = keys.len() as f64;
+    let m = 2f64.powi(bits as i32);
+    // n minus the expected number of distinct outputs; for wide outputs the
+    // birthday approximation avoids cancellation
+    let expected = if bits <= 32 { n - m * (1.0 - (-n / m).exp()) } else { n * (n - 1.0) / (2.0 * m) };
+
+    Collisions { keys: keys.len(), collisions, expected }
+}
+
+// Every key of 'len' bytes with at most 'max_bits' bits set
+pub fn sparse_keys(len: usize, max_bits: usize) -> Vec<Vec<u8>> {
+    fn extend(key: &mut Vec<u8>, from: usize, left: usize, keys: &mut Vec<Vec<u8>>) {
+        keys.push(key.clone());
+        if left == 0 {
+            return;
+        }
+        for i in from..key.len() * 8 {
+            key[i / 8] |= 1 << (i % 8);
+            extend(key, i + 1, left - 1, keys);
+            key[i / 8] &= !(1 << (i % 8));
+        }
+    }
+
+    let mut keys = vec![];
+    extend(&mut vec![0; len], 0, max_bits, &mut keys);
+    keys
+}
+
+// 'count' distinct keys, each a random block of 'cycle_len' bytes repeated
+// 'cycles' times
+pub fn cyclic_keys(count: usize, cycle_len: usize, cycles: usize, seed: u64) -> Vec<Vec<u8>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut seen = HashSet::with_capacity(count);
+    let mut keys = Vec::with_capacity(count);
+    while keys.len() < count {
+        let block = random_key(&mut rng, cycle_len);
+        if seen.insert(block.clone()) {
+            keys.push(block.repeat(cycles));
+        }
+    }
+    keys
+}
+
+// 'prefix' + every string of 'core_len' characters from 'alphabet' + 'suffix'
+pub fn text_keys(prefix: &str, alphabet: &[u8], core_len: usize, suffix: &str) -> Vec<Vec<u8>> {
+    let mut keys = vec![prefix.as_bytes().to_vec()];
+    for _ in 0..core_len {
+        keys = keys
+            .into_iter()
+            .flat_map(|key| {
+                alphabet.iter().map(move |&c| {
+                    let mut longer = key.clone();
+                    longer.push(c);
+                    longer
+                })
+            })
+            .collect();
+    }
+    for key in keys.iter_mut() {
+        key.extend_from_slice(suffix.as_bytes());
+    }
+    keys
+}
+
+pub struct KeysetResult {
+    pub name: &'static str,
+    pub distribution: Distribution,
+    pub collisions: Collisions,
+}
+
+// The whole battery with fixed sizes, for comparing hashers side by side
+pub struct QualityReport {
+    pub name: String,
+    pub output_bits: usize,
+    pub avalanche: Avalanche,
+    pub bit_independence: BitIndependence,
+    pub keysets: Vec<KeysetResult>,
+}
+
+impl QualityReport {
+    pub fn run<H: Hasher>(name: &str, hasher: &mut H, seed: u64) -> QualityReport
+    where
+        H::Output: HashOutput,
+    {
+        let keysets = [
+            // 8-byte keys with up to 3 bits set: 43745 keys
+            ("sparse", sparse_keys(8, 3)),
+            // 4-byte blocks repeated 8 times
+            ("cyclic", cyclic_keys(20_000, 4, 8, seed)),
+            // "Foo" + 3 lower-case letters or digits + "Bar": 46656 keys
+            ("text", text_keys("Foo", b"abcdefghijklmnopqrstuvwxyz0123456789", 3, "Bar")),
+        ];
+
+        let keysets = keysets
+            .into_iter()
+            .map(|(name, keys)| {
+                // About 8 keys per bucket, rounded down to a power of two
+                let wanted = (keys.len() / 8).max(2);
+                let buckets = 1 << (usize::BITS - 1 - wanted.leading_zeros());
+                KeysetResult {
+                    name,
+                    distribution: distribution(hasher, &keys, buckets),
+                    collisions: collisions(hasher, &keys),
+                }
+            })
+            .collect();
+
+        QualityReport {
+            name: name.to_string(),
+            output_bits: output_bits(hasher),
+            avalanche: avalanche(hasher, 8, 2000, seed),
+            bit_independence: bit_independence(hasher, 8, 500, seed),
+            keysets,
+        }
+    }
+}
+
+impl fmt::Display for QualityReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} ({}-bit output)", self.name, self.output_bits)?;
+
+        let (worst, i, j) = self.avalanche.worst_bias();
+        writeln!(
+            f,
+            "  avalanche, {} input bits x {} samples: worst bias {:.3} (input bit {}, output bit {}), mean bias {:.3}",
+            self.avalanche.input_bits,
+            self.avalanche.samples,
+            worst,
+            i,
+            j,
+            self.avalanche.mean_bias()
+        )?;
+
+        let bic = &self.bit_independence;
+        writeln!(
+            f,
+            "  bit independence, {} output bits x {} trials: worst bias {:.3} (output bits {} and {})",
+            bic.output_bits, bic.trials, bic.worst_bias, bic.worst_pair.0, bic.worst_pair.1
+        )?;
+
+        for keyset in &self.keysets {
+            let (dist, coll) = (&keyset.distribution, &keyset.collisions);
+            writeln!(
+                f,
+                "  {:<6} {:>6} keys: chi-square {:>10.1} over {:>5} buckets (z {:>8.2}), collisions {:>6} (expected {:.1})",
+                keyset.name,
+                dist.keys,
+                dist.chi_square,
+                dist.buckets,
+                dist.z_score(),
+                coll.collisions,
+                coll.expected
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Passes the first 8 key bytes through unchanged: no mixing at all
+    #[derive(Default)]
+    struct Identity {
+        buf: Vec<u8>,
+    }
+
+    impl Hasher for Identity {
+        type Output = u64;
+
+        fn update(&mut self, data: &[u8]) {
+            self.buf.extend_from_slice(data);
+        }
+
+        fn finalize(&mut self) -> u64 {
+            low_u64(&self.buf)
+        }
+
+        fn reset(&mut self) {
+            self.buf.clear();
+        }
+    }
+
+    #[test]
+    fn sparse_keyset() {
+        let keys = sparse_keys(2, 2);
+        // 1 + 16 + 16 * 15 / 2
+        assert_eq!(keys.len(), 137);
+        assert_eq!(keys.iter().collect::<HashSet<_>>().len(), keys.len());
+        assert!(keys.iter().all(|key| key.len() == 2 && key.iter().map(|b| b.count_ones()).sum::<u32>() <= 2));
+        assert_eq!(sparse_keys(8, 3).len(), 1 + 64 + 2016 + 41664);
+    }
+
+    #[test]
+    fn cyclic_keyset() {
+        let keys = cyclic_keys(1000, 3, 5, 1);
+        assert_eq!(keys.len(), 1000);
+        assert_eq!(keys.iter().collect::<HashSet<_>>().len(), keys.len());
+        for key in &keys {
+            assert_eq!(key.len(), 15);
+            assert!(key.chunks(3).all(|block| block == &key[..3]));
+        }
+        assert_eq!(keys, cyclic_keys(1000, 3, 5, 1));
+    }
+
+    #[test]
+    fn text_keyset() {
+        let keys = text_keys("Foo", b"ab", 3, "Bar");
+        assert_eq!(keys.len(), 8);
+        assert_eq!(keys[0], b"FooaaaBar");
+        assert_eq!(keys[5], b"FoobabBar");
+        assert_eq!(text_keys("", b"xyz", 0, ""), vec![Vec::<u8>::new()]);
+    }
+
+    #[test]
+    fn identity_fails_avalanche_and_independence() {
+        let mut identity = Identity::default();
+        let matrix = avalanche(&mut identity, 8, 50, 1);
+        assert_eq!((matrix.input_bits, matrix.output_bits), (64, 64));
+        // Each input bit moves exactly its own output bit
+        for i in 0..64 {
+            for j in 0..64 {
+                assert_eq!(matrix.probability(i, j), if i == j { 1.0 } else { 0.0 });
+            }
+        }
+        assert_eq!(matrix.mean_bias(), 1.0);
+        // Never changing is as biased as always changing
+        let rows = matrix.to_string();
+        assert!(rows.lines().all(|row| row.split_whitespace().nth(1) == Some(&"9".repeat(64)[..])));
+
+        // No two output bits ever change together
+        let bic = bit_independence(&mut identity, 8, 50, 1);
+        assert_eq!(bic.trials, 64 * 50);
+        assert_eq!(bic.worst_bias, 1.0);
+    }
+
+    #[test]
+    fn perfectly_even_distribution() {
+        let mut identity = Identity::default();
+        let keys: Vec<Vec<u8>> = (0u32..4096).map(|i| i.to_le_bytes().to_vec()).collect();
+        let even = distribution(&mut identity, &keys, 256);
+        assert_eq!(even.buckets, 256);
+        assert_eq!(even.chi_square, 0.0);
+        assert!(even.z_score() < -10.0);
+
+        // Every key a multiple of 256 lands in bucket 0
+        let keys: Vec<Vec<u8>> = (0u32..4096).map(|i| (i << 8).to_le_bytes().to_vec()).collect();
+        let skewed = distribution(&mut identity, &keys, 256);
+        assert_eq!(skewed.chi_square, 4096.0 * 255.0);
+        assert!(skewed.z_score() > 1000.0);
+
+        let result = collisions(&mut identity, &keys);
+        assert_eq!(result.collisions, 0);
+        assert!(result.expected < 1e-9);
+    }
+
+    #[cfg(feature = "pearson")]
+    #[test]
+    fn narrow_output_collisions() {
+        use crate::pearson::Pearson;
+
+        let mut pearson = Pearson::new();
+        let keys = cyclic_keys(1000, 4, 2, 3);
+        let result = collisions(&mut pearson, &keys);
+        // 1000 keys into 256 values: the first of each value is free
+        assert!(result.collisions >= 744);
+        assert!((result.expected - 749.1).abs() < 0.1);
+        // Bucket count is capped at the 256 possible outputs
+        assert_eq!(distribution(&mut pearson, &keys, 4096).buckets, 256);
+    }
+
+    #[cfg(feature = "djb2")]
+    #[test]
+    fn lose_lose_collides_on_anagrams() {
+        use crate::djb2::LoseLoseCipher;
+
+        let keys = text_keys("Foo", b"abcdefghijklmnopqrstuvwxyz", 3, "Bar");
+        let result = collisions(&mut LoseLoseCipher::new(), &keys);
+        // A byte sum only sees which letters occur: 3 + 25 * 3 + 1 sums
+        assert_eq!(result.collisions, keys.len() - 76);
+    }
+
+    #[cfg(feature = "adler32")]
+    #[test]
+    fn adler32_fails_avalanche() {
+        use crate::adler32::Adler32State;
+
+        let matrix = avalanche(&mut Adler32State::new(), 8, 200, 4);
+        let (worst, _, _) = matrix.worst_bias();
+        assert!(worst > 0.9, "worst bias {}", worst);
+    }
+
+    #[cfg(feature = "blake2b")]
+    #[test]
+    fn blake2b_passes() {
+        use crate::blake2b::BLAKE2b;
+
+        let mut blake = BLAKE2b::new(64, None).unwrap();
+        let matrix = avalanche(&mut blake, 8, 300, 5);
+        assert_eq!((matrix.input_bits, matrix.output_bits), (64, 512));
+        let (worst, _, _) = matrix.worst_bias();
+        assert!(worst < 0.35, "worst bias {}", worst);
+        assert!(matrix.mean_bias() < 0.06, "mean bias {}", matrix.mean_bias());
+
+        let bic = bit_independence(&mut blake, 8, 100, 5);
+        assert!(bic.worst_bias < 0.3, "worst bias {}", bic.worst_bias);
+
+        let keys = sparse_keys(4, 2);
+        let result = collisions(&mut blake, &keys);
+        assert_eq!(result.collisions, 0);
+        assert!(distribution(&mut blake, &keys, 64).z_score().abs() < 5.0);
+    }
+
+    #[test]
+    fn analysis_is_deterministic() {
+        let mut identity = Identity::default();
+        let a = avalanche(&mut identity, 4, 20, 9);
+        let b = avalanche(&mut identity, 4, 20, 9);
+        assert_eq!(a.flips, b.flips);
+    }
+
+    // Report only; run with `cargo test --release -- --ignored --nocapture`
+    #[cfg(all(
+        feature = "adler32",
+        feature = "blake2b",
+        feature = "crc32",
+        feature = "djb2",
+        feature = "fnv1a",
+        feature = "murmur3",
+        feature = "pearson",
+        feature = "xxhash"
+    ))]
+    #[test]
+    #[ignore]
+    fn quality_report() {
+        use crate::adler32::Adler32State;
+        use crate::blake2b::BLAKE2b;
+        use crate::crc32::CRC32State;
+        use crate::djb2::DJB2Cipher;
+        use crate::fnv1a::FNV1a;
+        use crate::murmur3::Murmur3x86_32;
+        use crate::pearson::Pearson;
+        use crate::xxhash::{XXH3_64, XXH64};
+
+        let reports = [
+            QualityReport::run("FNV-1a 32", &mut FNV1a::<u32>::new(), 24),
+            QualityReport::run("FNV-1a 64", &mut FNV1a::<u64>::new(), 24),
+            QualityReport::run("djb2", &mut DJB2Cipher::new(), 24),
+            QualityReport::run("Pearson", &mut Pearson::new(), 24),
+            QualityReport::run("CRC-32", &mut CRC32State::new(), 24),
+            QualityReport::run("Adler-32", &mut Adler32State::new(), 24),
+            QualityReport::run("BLAKE2b-512", &mut BLAKE2b::new(64, None).unwrap(), 24),
+            QualityReport::run("MurmurHash3 x86_32", &mut Murmur3x86_32::new(), 24),
+            QualityReport::run("XXH64", &mut XXH64::new(), 24),
+            QualityReport::run("XXH3-64", &mut XXH3_64::new(), 24),
+        ];
+        for report in &reports {
+            println!("{}", report);
+            println!("  avalanche matrix, input bits down, output bits across:");
+            print!("{}", report.avalanche);
+            println!();
+        }
+    }
+}
@@ -0,0 +1,1077 @@
+// xxHash by Yann Collet, following the reference xxhash.h (v0.8): XXH32,
+// XXH64, and XXH3 with its 64- and 128-bit outputs. XXH3 mixes the input
+// with a 192-byte secret, the default one shifted by the seed. XXH3-128
+// digests are returned as a u128 with the reference's high64 in the top
+// half, matching the canonical (xxhsum) byte order.
+
+use crate::hasher::{feed_blocks, Hasher};
+
+const PRIME32_1: u32 = 0x9E3779B1;
+const PRIME32_2: u32 = 0x85EBCA77;
+const PRIME32_3: u32 = 0xC2B2AE3D;
+const PRIME32_4: u32 = 0x27D4EB2F;
+const PRIME32_5: u32 = 0x165667B1;
+
+const PRIME64_1: u64 = 0x9E3779B185EBCA87;
+const PRIME64_2: u64 = 0xC2B2AE3D27D4EB4F;
+const PRIME64_3: u64 = 0x165667B19E3779F9;
+const PRIME64_4: u64 = 0x85EBCA77C2B2AE63;
+const PRIME64_5: u64 = 0x27D4EB2F165667C5;
+
+const PRIME_MX1: u64 = 0x165667919E3779F9;
+const PRIME_MX2: u64 = 0x9FB21C651E98DF25;
+
+fn read_u32(bytes: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], i: usize) -> u64 {
+    u64::from_le_bytes(bytes[i..i + 8].try_into().unwrap())
+}
+
+fn xxh32_round(acc: u32, input: u32) -> u32 {
+    acc.wrapping_add(input.wrapping_mul(PRIME32_2))
+        .rotate_left(13)
+        .wrapping_mul(PRIME32_1)
+}
+
+fn xxh64_round(acc: u64, input: u64) -> u64 {
+    acc.wrapping_add(input.wrapping_mul(PRIME64_2))
+        .rotate_left(31)
+        .wrapping_mul(PRIME64_1)
+}
+
+fn xxh64_merge_round(acc: u64, lane: u64) -> u64 {
+    (acc ^ xxh64_round(0, lane))
+        .wrapping_mul(PRIME64_1)
+        .wrapping_add(PRIME64_4)
+}
+
+fn xxh64_avalanche(mut h: u64) -> u64 {
+    h ^= h >> 33;
+    h = h.wrapping_mul(PRIME64_2);
+    h ^= h >> 29;
+    h = h.wrapping_mul(PRIME64_3);
+    h ^ (h >> 32)
+}
+
+pub struct XXH32 {
+    seed: u32,
+    lanes: [u32; 4],
+    buf: [u8; 16],
+    buf_len: usize,
+    total_len: u64,
+}
+
+impl XXH32 {
+    pub fn new() -> XXH32 {
+        XXH32::with_seed(0)
+    }
+
+    pub fn with_seed(seed: u32) -> XXH32 {
+        XXH32 {
+            seed,
+            lanes: [
+                seed.wrapping_add(PRIME32_1).wrapping_add(PRIME32_2),
+                seed.wrapping_add(PRIME32_2),
+                seed,
+                seed.wrapping_sub(PRIME32_1),
+            ],
+            buf: [0; 16],
+            buf_len: 0,
+            total_len: 0,
+        }
+    }
+
+    // One-shot hash with this hasher's seed; the streaming state is untouched
+    pub fn hash(&self, input: impl AsRef<[u8]>) -> u32 {
+        XXH32::with_seed(self.seed).digest(input.as_ref())
+    }
+}
+
+impl Default for XXH32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for XXH32 {
+    type Output = u32;
+
+    fn update(&mut self, data: &[u8]) {
+        self.total_len += data.len() as u64;
+        let lanes = &mut self.lanes;
+        feed_blocks(&mut self.buf, &mut self.buf_len, data, |stripe| {
+            for (i, lane) in lanes.iter_mut().enumerate() {
+                *lane = xxh32_round(*lane, read_u32(stripe, 4 * i));
+            }
+        });
+    }
+
+    fn finalize(&mut self) -> u32 {
+        let [v1, v2, v3, v4] = self.lanes;
+        let mut h = if self.total_len >= 16 {
+            v1.rotate_left(1)
+                .wrapping_add(v2.rotate_left(7))
+                .wrapping_add(v3.rotate_left(12))
+                .wrapping_add(v4.rotate_left(18))
+        } else {
+            self.seed.wrapping_add(PRIME32_5)
+        };
+        // Only the low 32 bits of the length take part
+        h = h.wrapping_add(self.total_len as u32);
+
+        let mut words = self.buf[..self.buf_len].chunks_exact(4);
+        for word in &mut words {
+            h = h.wrapping_add(read_u32(word, 0).wrapping_mul(PRIME32_3));
+            h = h.rotate_left(17).wrapping_mul(PRIME32_4);
+        }
+        for &byte in words.remainder() {
+            h = h.wrapping_add((byte as u32).wrapping_mul(PRIME32_5));
+            h = h.rotate_left(11).wrapping_mul(PRIME32_1);
+        }
+
+        h ^= h >> 15;
+        h = h.wrapping_mul(PRIME32_2);
+        h ^= h >> 13;
+        h = h.wrapping_mul(PRIME32_3);
+        h ^ (h >> 16)
+    }
+
+    fn reset(&mut self) {
+        *self = XXH32::with_seed(self.seed);
+    }
+}
+
+impl_io_write!(XXH32);
+
+pub struct XXH64 {
+    seed: u64,
+    lanes: [u64; 4],
+    buf: [u8; 32],
+    buf_len: usize,
+    total_len: u64,
+}
+
+impl XXH64 {
+    pub fn new() -> XXH64 {
+        XXH64::with_seed(0)
+    }
+
+    pub fn with_seed(seed: u64) -> XXH64 {
+        XXH64 {
+            seed,
+            lanes: [
+                seed.wrapping_add(PRIME64_1).wrapping_add(PRIME64_2),
+                seed.wrapping_add(PRIME64_2),
+                seed,
+                seed.wrapping_sub(PRIME64_1),
+            ],
+            buf: [0; 32],
+            buf_len: 0,
+            total_len: 0,
+        }
+    }
+
+    // One-shot hash with this hasher's seed; the streaming state is untouched
+    pub fn hash(&self, input: impl AsRef<[u8]>) -> u64 {
+        XXH64::with_seed(self.seed).digest(input.as_ref())
+    }
+}
+
+impl Default for XXH64 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for XXH64 {
+    type Output = u64;
+
+    fn update(&mut self, data: &[u8]) {
+        self.total_len += data.len() as u64;
+        let lanes = &mut self.lanes;
+        feed_blocks(&mut self.buf, &mut self.buf_len, data, |stripe| {
+            for (i, lane) in lanes.iter_mut().enumerate() {
+                *lane = xxh64_round(*lane, read_u64(stripe, 8 * i));
+            }
+        });
+    }
+
+    fn finalize(&mut self) -> u64 {
+        let [v1, v2, v3, v4] = self.lanes;
+        let mut h = if self.total_len >= 32 {
+            let h = v1
+                .rotate_left(1)
+                .wrapping_add(v2.rotate_left(7))
+                .wrapping_add(v3.rotate_left(12))
+                .wrapping_add(v4.rotate_left(18));
+            self.lanes
+                .iter()
+                .fold(h, |h, &lane| xxh64_merge_round(h, lane))
+        } else {
+            self.seed.wrapping_add(PRIME64_5)
+        };
+        h = h.wrapping_add(self.total_len);
+
+        let mut rest = &self.buf[..self.buf_len];
+        while rest.len() >= 8 {
+            h ^= xxh64_round(0, read_u64(rest, 0));
+            h = h
+                .rotate_left(27)
+                .wrapping_mul(PRIME64_1)
+                .wrapping_add(PRIME64_4);
+            rest = &rest[8..];
+        }
+        if rest.len() >= 4 {
+            h ^= (read_u32(rest, 0) as u64).wrapping_mul(PRIME64_1);
+            h = h
+                .rotate_left(23)
+                .wrapping_mul(PRIME64_2)
+                .wrapping_add(PRIME64_3);
+            rest = &rest[4..];
+        }
+        for &byte in rest {
+            h ^= (byte as u64).wrapping_mul(PRIME64_5);
+            h = h.rotate_left(11).wrapping_mul(PRIME64_1);
+        }
+
+        xxh64_avalanche(h)
+    }
+
+    fn reset(&mut self) {
+        *self = XXH64::with_seed(self.seed);
+    }
+}
+
+impl_io_write!(XXH64);
+
+// XXH3
+
+const SECRET_SIZE: usize = 192;
+const STRIPE_LEN: usize = 64;
+// Stripes accumulated between two scrambles, each shifting 8 bytes further
+// into the secret
+const STRIPES_PER_BLOCK: usize = (SECRET_SIZE - STRIPE_LEN) / 8;
+// Inputs up to this size never reach the stripe loop
+const MID_SIZE_MAX: usize = 240;
+const BUFFER_SIZE: usize = 256;
+
+const DEFAULT_SECRET: [u8; SECRET_SIZE] = [
+    0xb8, 0xfe, 0x6c, 0x39, 0x23, 0xa4, 0x4b, 0xbe, 0x7c, 0x01, 0x81, 0x2c, 0xf7, 0x21, 0xad, 0x1c,
+    0xde, 0xd4, 0x6d, 0xe9, 0x83, 0x90, 0x97, 0xdb, 0x72, 0x40, 0xa4, 0xa4, 0xb7, 0xb3, 0x67, 0x1f,
+    0xcb, 0x79, 0xe6, 0x4e, 0xcc, 0xc0, 0xe5, 0x78, 0x82, 0x5a, 0xd0, 0x7d, 0xcc, 0xff, 0x72, 0x21,
+    0xb8, 0x08, 0x46, 0x74, 0xf7, 0x43, 0x24, 0x8e, 0xe0, 0x35, 0x90, 0xe6, 0x81, 0x3a, 0x26, 0x4c,
+    0x3c, 0x28, 0x52, 0xbb, 0x91, 0xc3, 0x00, 0xcb, 0x88, 0xd0, 0x65, 0x8b, 0x1b, 0x53, 0x2e, 0xa3,
+    0x71, 0x64, 0x48, 0x97, 0xa2, 0x0d, 0xf9, 0x4e, 0x38, 0x19, 0xef, 0x46, 0xa9, 0xde, 0xac, 0xd8,
+    0xa8, 0xfa, 0x76, 0x3f, 0xe3, 0x9c, 0x34, 0x3f, 0xf9, 0xdc, 0xbb, 0xc7, 0xc7, 0x0b, 0x4f, 0x1d,
+    0x8a, 0x51, 0xe0, 0x4b, 0xcd, 0xb4, 0x59, 0x31, 0xc8, 0x9f, 0x7e, 0xc9, 0xd9, 0x78, 0x73, 0x64,
+    0xea, 0xc5, 0xac, 0x83, 0x34, 0xd3, 0xeb, 0xc3, 0xc5, 0x81, 0xa0, 0xff, 0xfa, 0x13, 0x63, 0xeb,
+    0x17, 0x0d, 0xdd, 0x51, 0xb7, 0xf0, 0xda, 0x49, 0xd3, 0x16, 0x55, 0x26, 0x29, 0xd4, 0x68, 0x9e,
+    0x2b, 0x16, 0xbe, 0x58, 0x7d, 0x47, 0xa1, 0xfc, 0x8f, 0xf8, 0xb8, 0xd1, 0x7a, 0xd0, 0x31, 0xce,
+    0x45, 0xcb, 0x3a, 0x8f, 0x95, 0x16, 0x04, 0x28, 0xaf, 0xd7, 0xfb, 0xca, 0xbb, 0x4b, 0x40, 0x7e,
+];
+
+fn xxh3_avalanche(mut h: u64) -> u64 {
+    h ^= h >> 37;
+    h = h.wrapping_mul(PRIME_MX1);
+    h ^ (h >> 32)
+}
+
+// Stronger finisher for 4 to 8 byte inputs
+fn rrmxmx(mut h: u64, len: u64) -> u64 {
+    h ^= h.rotate_left(49) ^ h.rotate_left(24);
+    h = h.wrapping_mul(PRIME_MX2);
+    h ^= (h >> 35).wrapping_add(len);
+    h = h.wrapping_mul(PRIME_MX2);
+    h ^ (h >> 28)
+}
+
+// Full 64x64 -> 128-bit product as (low, high)
+fn mul_128(a: u64, b: u64) -> (u64, u64) {
+    let product = a as u128 * b as u128;
+    (product as u64, (product >> 64) as u64)
+}
+
+fn mul128_fold64(a: u64, b: u64) -> u64 {
+    let (low, high) = mul_128(a, b);
+    low ^ high
+}
+
+fn mix_16b(input: &[u8], secret: &[u8], seed: u64) -> u64 {
+    mul128_fold64(
+        read_u64(input, 0) ^ read_u64(secret, 0).wrapping_add(seed),
+        read_u64(input, 8) ^ read_u64(secret, 8).wrapping_sub(seed),
+    )
+}
+
+// 128-bit counterpart of mix_16b, feeding two 16-byte inputs crosswise
+// into the two halves of 'acc'
+fn mix_32b(acc: &mut (u64, u64), input_1: &[u8], input_2: &[u8], secret: &[u8], seed: u64) {
+    acc.0 = acc.0.wrapping_add(mix_16b(input_1, secret, seed));
+    acc.0 ^= read_u64(input_2, 0).wrapping_add(read_u64(input_2, 8));
+    acc.1 = acc.1.wrapping_add(mix_16b(input_2, &secret[16..], seed));
+    acc.1 ^= read_u64(input_1, 0).wrapping_add(read_u64(input_1, 8));
+}
+
+fn xxh3_64_short(input: &[u8], secret: &[u8], seed: u64) -> u64 {
+    let len = input.len();
+    match len {
+        0 => xxh64_avalanche(seed ^ read_u64(secret, 56) ^ read_u64(secret, 64)),
+        1..=3 => {
+            let combined = (input[0] as u32) << 16
+                | (input[len >> 1] as u32) << 24
+                | input[len - 1] as u32
+                | (len as u32) << 8;
+            let bitflip = ((read_u32(secret, 0) ^ read_u32(secret, 4)) as u64).wrapping_add(seed);
+            xxh64_avalanche(combined as u64 ^ bitflip)
+        }
+        4..=8 => {
+            let seed = seed ^ ((seed as u32).swap_bytes() as u64) << 32;
+            let bitflip = (read_u64(secret, 8) ^ read_u64(secret, 16)).wrapping_sub(seed);
+            let input64 =
+                (read_u32(input, len - 4) as u64).wrapping_add((read_u32(input, 0) as u64) << 32);
+            rrmxmx(input64 ^ bitflip, len as u64)
+        }
+        9..=16 => {
+            let bitflip_1 = (read_u64(secret, 24) ^ read_u64(secret, 32)).wrapping_add(seed);
+            let bitflip_2 = (read_u64(secret, 40) ^ read_u64(secret, 48)).wrapping_sub(seed);
+            let low = read_u64(input, 0) ^ bitflip_1;
+            let high = read_u64(input, len - 8) ^ bitflip_2;
+            let acc = (len as u64)
+                .wrapping_add(low.swap_bytes())
+                .wrapping_add(high)
+                .wrapping_add(mul128_fold64(low, high));
+            xxh3_avalanche(acc)
+        }
+        17..=128 => {
+            // Pairs of 16-byte blocks working inwards from both ends
+            let mut acc = (len as u64).wrapping_mul(PRIME64_1);
+            for i in 0..(len - 1) / 32 + 1 {
+                acc = acc.wrapping_add(mix_16b(&input[16 * i..], &secret[32 * i..], seed));
+                acc = acc.wrapping_add(mix_16b(
+                    &input[len - 16 * (i + 1)..],
+                    &secret[32 * i + 16..],
+                    seed,
+                ));
+            }
+            xxh3_avalanche(acc)
+        }
+        _ => {
+            let mut acc = (len as u64).wrapping_mul(PRIME64_1);
+            for i in 0..8 {
+                acc = acc.wrapping_add(mix_16b(&input[16 * i..], &secret[16 * i..], seed));
+            }
+            acc = xxh3_avalanche(acc);
+            for i in 8..len / 16 {
+                acc =
+                    acc.wrapping_add(mix_16b(&input[16 * i..], &secret[16 * (i - 8) + 3..], seed));
+            }
+            acc = acc.wrapping_add(mix_16b(&input[len - 16..], &secret[136 - 17..], seed));
+            xxh3_avalanche(acc)
+        }
+    }
+}
+
+// Shared ending of the 17 to 240 byte XXH3-128 paths
+fn xxh3_128_finish(acc: (u64, u64), len: usize, seed: u64) -> (u64, u64) {
+    let low = acc.0.wrapping_add(acc.1);
+    let high = acc
+        .0
+        .wrapping_mul(PRIME64_1)
+        .wrapping_add(acc.1.wrapping_mul(PRIME64_4))
+        .wrapping_add((len as u64).wrapping_sub(seed).wrapping_mul(PRIME64_2));
+    (xxh3_avalanche(low), 0u64.wrapping_sub(xxh3_avalanche(high)))
+}
+
+// XXH3-128 of up to MID_SIZE_MAX bytes as (low, high)
+fn xxh3_128_short(input: &[u8], secret: &[u8], seed: u64) -> (u64, u64) {
+    let len = input.len();
+    match len {
+        0 => (
+            xxh64_avalanche(seed ^ read_u64(secret, 64) ^ read_u64(secret, 72)),
+            xxh64_avalanche(seed ^ read_u64(secret, 80) ^ read_u64(secret, 88)),
+        ),
+        1..=3 => {
+            let combined_low = (input[0] as u32) << 16
+                | (input[len >> 1] as u32) << 24
+                | input[len - 1] as u32
+                | (len as u32) << 8;
+            let combined_high = combined_low.swap_bytes().rotate_left(13);
+            let bitflip_low =
+                ((read_u32(secret, 0) ^ read_u32(secret, 4)) as u64).wrapping_add(seed);
+            let bitflip_high =
+                ((read_u32(secret, 8) ^ read_u32(secret, 12)) as u64).wrapping_sub(seed);
+            (
+                xxh64_avalanche(combined_low as u64 ^ bitflip_low),
+                xxh64_avalanche(combined_high as u64 ^ bitflip_high),
+            )
+        }
+        4..=8 => {
+            let seed = seed ^ ((seed as u32).swap_bytes() as u64) << 32;
+            let input64 =
+                (read_u32(input, 0) as u64).wrapping_add((read_u32(input, len - 4) as u64) << 32);
+            let bitflip = (read_u64(secret, 16) ^ read_u64(secret, 24)).wrapping_add(seed);
+            let (mut low, mut high) =
+                mul_128(input64 ^ bitflip, PRIME64_1.wrapping_add((len as u64) << 2));
+            high = high.wrapping_add(low << 1);
+            low ^= high >> 3;
+            low ^= low >> 35;
+            low = low.wrapping_mul(PRIME_MX2);
+            low ^= low >> 28;
+            (low, xxh3_avalanche(high))
+        }
+        9..=16 => {
+            let bitflip_low = (read_u64(secret, 32) ^ read_u64(secret, 40)).wrapping_sub(seed);
+            let bitflip_high = (read_u64(secret, 48) ^ read_u64(secret, 56)).wrapping_add(seed);
+            let input_low = read_u64(input, 0);
+            let mut input_high = read_u64(input, len - 8);
+            let (mut low, mut high) = mul_128(input_low ^ input_high ^ bitflip_low, PRIME64_1);
+            low = low.wrapping_add(((len - 1) as u64) << 54);
+            input_high ^= bitflip_high;
+            high = high
+                .wrapping_add(input_high)
+                .wrapping_add((input_high as u32 as u64).wrapping_mul((PRIME32_2 - 1) as u64));
+            low ^= high.swap_bytes();
+            let (h_low, h_high) = mul_128(low, PRIME64_2);
+            (
+                xxh3_avalanche(h_low),
+                xxh3_avalanche(h_high.wrapping_add(high.wrapping_mul(PRIME64_2))),
+            )
+        }
+        17..=128 => {
+            let mut acc = ((len as u64).wrapping_mul(PRIME64_1), 0);
+            for i in (0..(len - 1) / 32 + 1).rev() {
+                mix_32b(
+                    &mut acc,
+                    &input[16 * i..],
+                    &input[len - 16 * (i + 1)..],
+                    &secret[32 * i..],
+                    seed,
+                );
+            }
+            xxh3_128_finish(acc, len, seed)
+        }
+        _ => {
+            let mut acc = ((len as u64).wrapping_mul(PRIME64_1), 0);
+            for i in 0..4 {
+                mix_32b(
+                    &mut acc,
+                    &input[32 * i..],
+                    &input[32 * i + 16..],
+                    &secret[32 * i..],
+                    seed,
+                );
+            }
+            acc = (xxh3_avalanche(acc.0), xxh3_avalanche(acc.1));
+            for i in 4..len / 32 {
+                mix_32b(
+                    &mut acc,
+                    &input[32 * i..],
+                    &input[32 * i + 16..],
+                    &secret[32 * (i - 4) + 3..],
+                    seed,
+                );
+            }
+            mix_32b(
+                &mut acc,
+                &input[len - 16..],
+                &input[len - 32..],
+                &secret[136 - 17 - 16..],
+                0u64.wrapping_sub(seed),
+            );
+            xxh3_128_finish(acc, len, seed)
+        }
+    }
+}
+
+fn accumulate_512(acc: &mut [u64; 8], stripe: &[u8], secret: &[u8]) {
+    for i in 0..8 {
+        let data = read_u64(stripe, 8 * i);
+        let key = data ^ read_u64(secret, 8 * i);
+        acc[i ^ 1] = acc[i ^ 1].wrapping_add(data);
+        acc[i] = acc[i].wrapping_add((key & 0xFFFFFFFF).wrapping_mul(key >> 32));
+    }
+}
+
+fn scramble(acc: &mut [u64; 8], secret: &[u8]) {
+    for (i, lane) in acc.iter_mut().enumerate() {
+        *lane ^= *lane >> 47;
+        *lane ^= read_u64(secret, 8 * i);
+        *lane = lane.wrapping_mul(PRIME32_1 as u64);
+    }
+}
+
+fn merge_accs(acc: &[u64; 8], secret: &[u8], start: u64) -> u64 {
+    let mut result = start;
+    for i in 0..4 {
+        result = result.wrapping_add(mul128_fold64(
+            acc[2 * i] ^ read_u64(secret, 16 * i),
+            acc[2 * i + 1] ^ read_u64(secret, 16 * i + 8),
+        ));
+    }
+    xxh3_avalanche(result)
+}
+
+// Streaming state behind XXH3_64 and XXH3_128, which only differ in how
+// they finish. Input longer than MID_SIZE_MAX is accumulated 64-byte stripe
+// by stripe; the buffer always keeps at least one byte back, because the
+// last stripe of the input is mixed differently.
+struct XXH3State {
+    seed: u64,
+    secret: [u8; SECRET_SIZE],
+    acc: [u64; 8],
+    buf: [u8; BUFFER_SIZE],
+    buf_len: usize,
+    // End of the last consumed buffer, for a final stripe reaching back
+    // before the current one
+    prev_stripe: [u8; STRIPE_LEN],
+    stripes_in_block: usize,
+    total_len: u64,
+}
+
+impl XXH3State {
+    fn new(seed: u64) -> XXH3State {
+        let mut secret = DEFAULT_SECRET;
+        for (i, word) in secret.chunks_exact_mut(8).enumerate() {
+            let value = read_u64(&DEFAULT_SECRET, 8 * i);
+            let value = if i % 2 == 0 {
+                value.wrapping_add(seed)
+            } else {
+                value.wrapping_sub(seed)
+            };
+            word.copy_from_slice(&value.to_le_bytes());
+        }
+
+        XXH3State {
+            seed,
+            secret,
+            acc: [
+                PRIME32_3 as u64,
+                PRIME64_1,
+                PRIME64_2,
+                PRIME64_3,
+                PRIME64_4,
+                PRIME32_2 as u64,
+                PRIME64_5,
+                PRIME32_1 as u64,
+            ],
+            buf: [0; BUFFER_SIZE],
+            buf_len: 0,
+            prev_stripe: [0; STRIPE_LEN],
+            stripes_in_block: 0,
+            total_len: 0,
+        }
+    }
+
+    fn consume_stripes(
+        acc: &mut [u64; 8],
+        stripes_in_block: &mut usize,
+        data: &[u8],
+        secret: &[u8; SECRET_SIZE],
+    ) {
+        for stripe in data.chunks_exact(STRIPE_LEN) {
+            accumulate_512(acc, stripe, &secret[8 * *stripes_in_block..]);
+            *stripes_in_block += 1;
+            if *stripes_in_block == STRIPES_PER_BLOCK {
+                scramble(acc, &secret[SECRET_SIZE - STRIPE_LEN..]);
+                *stripes_in_block = 0;
+            }
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u64;
+        while !data.is_empty() {
+            // A full buffer is only consumed once more input shows it does
+            // not hold the last stripe
+            if self.buf_len == BUFFER_SIZE {
+                XXH3State::consume_stripes(
+                    &mut self.acc,
+                    &mut self.stripes_in_block,
+                    &self.buf,
+                    &self.secret,
+                );
+                self.prev_stripe
+                    .copy_from_slice(&self.buf[BUFFER_SIZE - STRIPE_LEN..]);
+                self.buf_len = 0;
+            }
+            let take = (BUFFER_SIZE - self.buf_len).min(data.len());
+            self.buf[self.buf_len..self.buf_len + take].copy_from_slice(&data[..take]);
+            self.buf_len += take;
+            data = &data[take..];
+        }
+    }
+
+    // Accumulators after the whole input, for inputs over MID_SIZE_MAX
+    fn long_acc(&self) -> [u64; 8] {
+        let mut acc = self.acc;
+        let mut stripes_in_block = self.stripes_in_block;
+        let rest = &self.buf[..self.buf_len];
+        let stripes = (rest.len() - 1) / STRIPE_LEN;
+        XXH3State::consume_stripes(
+            &mut acc,
+            &mut stripes_in_block,
+            &rest[..stripes * STRIPE_LEN],
+            &self.secret,
+        );
+
+        let mut last = [0u8; STRIPE_LEN];
+        if rest.len() >= STRIPE_LEN {
+            last.copy_from_slice(&rest[rest.len() - STRIPE_LEN..]);
+        } else {
+            let reach_back = STRIPE_LEN - rest.len();
+            last[..reach_back].copy_from_slice(&self.prev_stripe[rest.len()..]);
+            last[reach_back..].copy_from_slice(rest);
+        }
+        accumulate_512(
+            &mut acc,
+            &last,
+            &self.secret[SECRET_SIZE - STRIPE_LEN - 7..],
+        );
+        acc
+    }
+
+    fn is_short(&self) -> bool {
+        self.total_len <= MID_SIZE_MAX as u64
+    }
+
+    fn short_input(&self) -> &[u8] {
+        &self.buf[..self.buf_len]
+    }
+}
+
+pub struct XXH3_64 {
+    state: XXH3State,
+}
+
+impl XXH3_64 {
+    pub fn new() -> XXH3_64 {
+        XXH3_64::with_seed(0)
+    }
+
+    pub fn with_seed(seed: u64) -> XXH3_64 {
+        XXH3_64 {
+            state: XXH3State::new(seed),
+        }
+    }
+
+    // One-shot hash with this hasher's seed; the streaming state is untouched
+    pub fn hash(&self, input: impl AsRef<[u8]>) -> u64 {
+        XXH3_64::with_seed(self.state.seed).digest(input.as_ref())
+    }
+}
+
+impl Default for XXH3_64 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for XXH3_64 {
+    type Output = u64;
+
+    fn update(&mut self, data: &[u8]) {
+        self.state.update(data);
+    }
+
+    fn finalize(&mut self) -> u64 {
+        let state = &self.state;
+        if state.is_short() {
+            // Short inputs mix in the seed directly, not the shifted secret
+            return xxh3_64_short(state.short_input(), &DEFAULT_SECRET, state.seed);
+        }
+        let start = state.total_len.wrapping_mul(PRIME64_1);
+        merge_accs(&state.long_acc(), &state.secret[11..], start)
+    }
+
+    fn reset(&mut self) {
+        self.state = XXH3State::new(self.state.seed);
+    }
+}
+
+impl_io_write!(XXH3_64);
+
+pub struct XXH3_128 {
+    state: XXH3State,
+}
+
+impl XXH3_128 {
+    pub fn new() -> XXH3_128 {
+        XXH3_128::with_seed(0)
+    }
+
+    pub fn with_seed(seed: u64) -> XXH3_128 {
+        XXH3_128 {
+            state: XXH3State::new(seed),
+        }
+    }
+
+    // One-shot hash with this hasher's seed; the streaming state is untouched
+    pub fn hash(&self, input: impl AsRef<[u8]>) -> u128 {
+        XXH3_128::with_seed(self.state.seed).digest(input.as_ref())
+    }
+}
+
+impl Default for XXH3_128 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for XXH3_128 {
+    type Output = u128;
+
+    fn update(&mut self, data: &[u8]) {
+        self.state.update(data);
+    }
+
+    fn finalize(&mut self) -> u128 {
+        let state = &self.state;
+        let (low, high) = if state.is_short() {
+            xxh3_128_short(state.short_input(), &DEFAULT_SECRET, state.seed)
+        } else {
+            let acc = state.long_acc();
+            (
+                merge_accs(
+                    &acc,
+                    &state.secret[11..],
+                    state.total_len.wrapping_mul(PRIME64_1),
+                ),
+                merge_accs(
+                    &acc,
+                    &state.secret[SECRET_SIZE - 64 - 11..],
+                    !state.total_len.wrapping_mul(PRIME64_2),
+                ),
+            )
+        };
+        (high as u128) << 64 | low as u128
+    }
+
+    fn reset(&mut self) {
+        self.state = XXH3State::new(self.state.seed);
+    }
+}
+
+impl_io_write!(XXH3_128);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hasher::digest_chunked;
+    use std::io::{self, Cursor};
+    use std::time::Instant;
+
+    // xxhsum's 64-bit sanity constant: 11400714785074694797, six more than
+    // PRIME64_1
+    const SANITY_PRIME64: u64 = 0x9E3779B185EBCA8D;
+
+    // The buffer xxhsum's sanity check hashes: the top byte of a running
+    // product of PRIME32_1 and SANITY_PRIME64
+    fn sanity_buffer(len: usize) -> Vec<u8> {
+        let mut generator = PRIME32_1 as u64;
+        (0..len)
+            .map(|_| {
+                let byte = (generator >> 56) as u8;
+                generator = generator.wrapping_mul(SANITY_PRIME64);
+                byte
+            })
+            .collect()
+    }
+
+    // (length, seed 0, seed PRIME32_1) from xxhsum's sanity check
+    const XXH32_SANITY: [(usize, u32, u32); 4] = [
+        (0, 0x02CC5D05, 0x36B78AE7),
+        (1, 0xCF65B03E, 0xB4545AA4),
+        (14, 0x1208E7E2, 0x6AF1D1FE),
+        (222, 0x5BD11DBD, 0x58803C5F),
+    ];
+
+    const XXH64_SANITY: [(usize, u64, u64); 4] = [
+        (0, 0xEF46DB3751D8E999, 0xAC75FDA2929B17EF),
+        (1, 0xE934A84ADB052768, 0x5014607643A9B4C3),
+        (14, 0x8282DCC4994E35C8, 0xC3BD6BF63DEB6DF0),
+        (222, 0xB641AE8CB691C174, 0x20CB8AB7AE10C14A),
+    ];
+
+    // (length, seed 0, seed SANITY_PRIME64); the lengths reach every size class
+    // from empty through the multi-block long path
+    const XXH3_64_SANITY: [(usize, u64, u64); 13] = [
+        (0, 0x2D06800538D394C2, 0xA8A6B918B2F0364A),
+        (1, 0xC44BDFF4074EECDB, 0x032BE332DD766EF8),
+        (6, 0x27B56A84CD2D7325, 0x84589C116AB59AB9),
+        (12, 0xA713DAF0DFBB77E7, 0xE7303E1B2336DE0E),
+        (24, 0xA3FE70BF9D3510EB, 0x850E80FC35BDD690),
+        (48, 0x397DA259ECBA1F11, 0xADC2CBAA44ACC616),
+        (80, 0xBCDEFBBB2C47C90A, 0xC6DD0CB699532E73),
+        (195, 0xCD94217EE362EC3A, 0xBA68003D370CB3D9),
+        (403, 0xCDEB804D65C6DEA4, 0x6259F6ECFD6443FD),
+        (512, 0x617E49599013CB6B, 0x3CE457DE14C27708),
+        (2048, 0xDD59E2C3A5F038E0, 0x66F81670669ABABC),
+        (2240, 0x6E73A90539CF2948, 0x757BA8487D1B5247),
+        (2367, 0xCB37AEB9E5D361ED, 0xD2DB3415B942B42A),
+    ];
+
+    const XXH3_128_SANITY: [(usize, u128, u128); 13] = [
+        (
+            0,
+            0x99AA06D3014798D8_6001C324468D497F,
+            0x00FEAA732A3CE25E_A986DFC5D7605BFE,
+        ),
+        (
+            1,
+            0xA6CD5E9392000F6A_C44BDFF4074EECDB,
+            0x20E49ABCC53B3842_032BE332DD766EF8,
+        ),
+        (
+            6,
+            0x082AFE0B8162D12A_3E7039BDDA43CFC6,
+            0x014BD95A51CA5DDB_C5B54D56038E4E40,
+        ),
+        (
+            12,
+            0x6E3EFD8FC7802B18_061A192713F69AD9,
+            0xFF0D60ACD02ED401_5D92B5D7190B12D1,
+        ),
+        (
+            24,
+            0x0CE966E4678D3761_1E7044D28B1B901D,
+            0xD7895DED1F62559D_C6CBF92A70680B19,
+        ),
+        (
+            48,
+            0xA002AC4E5478227E_F942219AED80F67B,
+            0xBC689F4C0152FB44_3A94D91333ED395A,
+        ),
+        (
+            80,
+            0xFDF2CEFDE9EAAC8A_454AE6BF7A8A532D,
+            0x19BF02D69BC56833_A5EAC764D1FF1166,
+        ),
+        (
+            195,
+            0x7729543A26B207EE_3FB593C086A66075,
+            0x0326104C4D4849E7_CF9D9EC2C8C9913F,
+        ),
+        (
+            403,
+            0x1B6DE21E332DD73D_CDEB804D65C6DEA4,
+            0xBED311971E0BE8F2_6259F6ECFD6443FD,
+        ),
+        (
+            512,
+            0x18D2D110DCC9BCA1_617E49599013CB6B,
+            0x925D06B8EC5B8040_3CE457DE14C27708,
+        ),
+        (
+            2048,
+            0xF736557FD47073A5_DD59E2C3A5F038E0,
+            0x23CC3A2E75EBAAEA_66F81670669ABABC,
+        ),
+        (
+            2240,
+            0xCCB134FBFA7CE49D_6E73A90539CF2948,
+            0xE40842F585875BA9_757BA8487D1B5247,
+        ),
+        (
+            2367,
+            0xE89C0F6FF369B427_CB37AEB9E5D361ED,
+            0xCCB7A94CCA1A6496_D2DB3415B942B42A,
+        ),
+    ];
+
+    // (input, XXH32, XXH64, XXH3-64), seed 0
+    const KNOWN_ANSWERS: [(&str, u32, u64, u64); 3] = [
+        ("", 0x02CC5D05, 0xEF46DB3751D8E999, 0x2D06800538D394C2),
+        ("abc", 0x32D153FF, 0x44BC2CF5AD770999, 0x78AF5F94892F3950),
+        (
+            "The quick brown fox jumps over the lazy dog",
+            0xE85EA4DE,
+            0x0B242D361FDA71BC,
+            0xCE7D19A5418FB365,
+        ),
+    ];
+
+    #[test]
+    fn xxh32_sanity() {
+        let buffer = sanity_buffer(2367);
+        for (len, unseeded, seeded) in XXH32_SANITY {
+            assert_eq!(
+                XXH32::new().hash(&buffer[..len]),
+                unseeded,
+                "length {}",
+                len
+            );
+            assert_eq!(
+                XXH32::with_seed(PRIME32_1).hash(&buffer[..len]),
+                seeded,
+                "length {}",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn xxh64_sanity() {
+        let buffer = sanity_buffer(2367);
+        for (len, unseeded, seeded) in XXH64_SANITY {
+            assert_eq!(
+                XXH64::new().hash(&buffer[..len]),
+                unseeded,
+                "length {}",
+                len
+            );
+            assert_eq!(
+                XXH64::with_seed(PRIME32_1 as u64).hash(&buffer[..len]),
+                seeded,
+                "length {}",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn xxh3_64_sanity() {
+        let buffer = sanity_buffer(2367);
+        for (len, unseeded, seeded) in XXH3_64_SANITY {
+            assert_eq!(
+                XXH3_64::new().hash(&buffer[..len]),
+                unseeded,
+                "length {}",
+                len
+            );
+            assert_eq!(
+                XXH3_64::with_seed(SANITY_PRIME64).hash(&buffer[..len]),
+                seeded,
+                "length {}",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn xxh3_128_sanity() {
+        let buffer = sanity_buffer(2367);
+        for (len, unseeded, seeded) in XXH3_128_SANITY {
+            assert_eq!(
+                XXH3_128::new().hash(&buffer[..len]),
+                unseeded,
+                "length {}",
+                len
+            );
+            assert_eq!(
+                XXH3_128::with_seed(SANITY_PRIME64).hash(&buffer[..len]),
+                seeded,
+                "length {}",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn known_answers() {
+        for (input, xxh32, xxh64, xxh3) in KNOWN_ANSWERS {
+            assert_eq!(XXH32::new().hash(input), xxh32, "{:?}", input);
+            assert_eq!(XXH64::new().hash(input), xxh64, "{:?}", input);
+            assert_eq!(XXH3_64::new().hash(input), xxh3, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn streaming_matches_one_shot() {
+        // Lengths around every size class, the 256-byte buffer and the
+        // 1024-byte XXH3 block, in chunks that straddle those boundaries
+        let buffer = sanity_buffer(4200);
+        let lengths = (0..=300).chain([
+            511, 512, 513, 1023, 1024, 1025, 1087, 1088, 1089, 2048, 2049, 4200,
+        ]);
+        for len in lengths {
+            let input = &buffer[..len];
+            let xxh32 = XXH32::with_seed(9).hash(input);
+            let xxh64 = XXH64::with_seed(9).hash(input);
+            let xxh3_64 = XXH3_64::with_seed(9).hash(input);
+            let xxh3_128 = XXH3_128::with_seed(9).hash(input);
+            for chunk in [1, 7, 63, 64, 65, 255, 256, 257] {
+                assert_eq!(
+                    digest_chunked(&mut XXH32::with_seed(9), input, chunk),
+                    xxh32,
+                    "len {}",
+                    len
+                );
+                assert_eq!(
+                    digest_chunked(&mut XXH64::with_seed(9), input, chunk),
+                    xxh64,
+                    "len {}",
+                    len
+                );
+                assert_eq!(
+                    digest_chunked(&mut XXH3_64::with_seed(9), input, chunk),
+                    xxh3_64,
+                    "len {}",
+                    len
+                );
+                assert_eq!(
+                    digest_chunked(&mut XXH3_128::with_seed(9), input, chunk),
+                    xxh3_128,
+                    "len {}",
+                    len
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn hasher_reset_keeps_seed() {
+        let buffer = sanity_buffer(2048);
+        let mut xxh3 = XXH3_64::with_seed(SANITY_PRIME64);
+        xxh3.update(&buffer);
+        assert_eq!(xxh3.digest(&buffer[..403]), 0x6259F6ECFD6443FD);
+
+        let mut xxh32 = XXH32::with_seed(PRIME32_1);
+        xxh32.update(b"stale");
+        assert_eq!(xxh32.digest(&buffer[..222]), 0x58803C5F);
+    }
+
+    #[test]
+    fn reader_and_io_copy() {
+        let buffer = sanity_buffer(2367);
+        let mut xxh64 = XXH64::new();
+        assert_eq!(
+            xxh64.digest_reader(Cursor::new(&buffer[..222])).unwrap(),
+            0xB641AE8CB691C174
+        );
+
+        let mut copied = XXH3_128::new();
+        io::copy(&mut Cursor::new(&buffer), &mut copied).unwrap();
+        assert_eq!(copied.finalize(), 0xE89C0F6FF369B427_CB37AEB9E5D361ED);
+    }
+
+    // Timing only; run with `cargo test --release -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_xxhash() {
+        let input = sanity_buffer(16 * 1024 * 1024);
+        let mb = input.len() as f64 / (1024.0 * 1024.0);
+
+        type Variant = (&'static str, fn(&[u8]) -> u128);
+        let variants: [Variant; 4] = [
+            ("XXH32", |input| XXH32::new().hash(input) as u128),
+            ("XXH64", |input| XXH64::new().hash(input) as u128),
+            ("XXH3-64", |input| XXH3_64::new().hash(input) as u128),
+            ("XXH3-128", |input| XXH3_128::new().hash(input)),
+        ];
+
+        for (name, f) in variants.iter() {
+            let start = Instant::now();
+            let hash = f(&input);
+            let elapsed = start.elapsed().as_secs_f64();
+            println!(
+                "{:>10}: {:032X} in {:.3}s ({:.1} MB/s)",
+                name,
+                hash,
+                elapsed,
+                mb / elapsed
+            );
+        }
+    }
+}